@@ -1,6 +1,11 @@
+// Every instruction and helper returns anchor's `Result`, whose error type is large by design
+#![allow(clippy::result_large_err)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar::slot_hashes;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use std::collections::HashMap;
 
 declare_id!("Poker11111111111111111111111111111111111111");
 
@@ -9,12 +14,29 @@ pub mod poker_game {
     use super::*;
 
     /// Initialize the poker game program with global settings
-    pub fn initialize(ctx: Context<Initialize>, fee_percentage: u8) -> Result<()> {
+    ///
+    /// Cash pots are raked at `fee_percentage`, up to `rake_cap_big_blinds` big blinds a hand
+    /// (0 = uncapped).
+    pub fn initialize(ctx: Context<Initialize>, fee_percentage: u8, rake_cap_big_blinds: u8) -> Result<()> {
         require!(fee_percentage <= 10, ErrorCode::FeeTooHigh); // Max 10% fee
 
         let game_authority = &mut ctx.accounts.game_authority;
         game_authority.authority = ctx.accounts.authority.key();
         game_authority.fee_percentage = fee_percentage;
+        game_authority.rake_cap_big_blinds = rake_cap_big_blinds;
+        game_authority.protocol_fee_bps = 10_000;
+        game_authority.host_fee_bps = 0;
+        game_authority.referral_fee_bps = 0;
+        game_authority.protocol_fees = 0;
+        game_authority.referral_fees = 0;
+        game_authority.pending_authority = Pubkey::default();
+        game_authority.signers = Vec::new();
+        game_authority.threshold = 0;
+        game_authority.paused = false;
+        game_authority.pause_scope = 0;
+        game_authority.oracles = Vec::new();
+        game_authority.dealers = Vec::new();
+        game_authority.fee_mint = ctx.accounts.fee_mint.key();
         game_authority.total_games_played = 0;
         game_authority.total_fees_collected = 0;
         game_authority.bump = *ctx.bumps.get("game_authority").unwrap();
@@ -22,36 +44,361 @@ pub mod poker_game {
         Ok(())
     }
 
+    /// Grow a game authority written by the first release into the current layout (authority only)
+    ///
+    /// The fee split, signer set, pause, oracle and dealer settings start out as `initialize`
+    /// leaves them, and fees are held in `fee_mint` from now on. The authority pays the extra rent.
+    pub fn migrate_game_authority(ctx: Context<MigrateGameAuthority>) -> Result<()> {
+        let account = ctx.accounts.game_authority.to_account_info();
+        require!(account.owner == &crate::ID, ErrorCode::InvalidGameAuthority);
+        let legacy = read_legacy_game_authority(&account.try_borrow_data()?)?;
+        require!(legacy.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        
+        // Top the account up to rent exemption at its new size before growing it
+        let new_len = 8 + GameAuthority::SIZE;
+        let rent = Rent::get()?.minimum_balance(new_len);
+        let shortfall = rent.saturating_sub(account.lamports());
+        if shortfall > 0 {
+            let cpi_accounts = anchor_lang::system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: account.clone(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+            anchor_lang::system_program::transfer(cpi_ctx, shortfall)?;
+        }
+        account.realloc(new_len, true)?;
+        
+        let game_authority = migrated_game_authority(&legacy, ctx.accounts.fee_mint.key());
+        let mut data = account.try_borrow_mut_data()?;
+        game_authority.try_serialize(&mut &mut data[..])?;
+        
+        Ok(())
+    }
+
+    /// Change the rake percentage charged at cash tables created from now on (authority only)
+    pub fn update_fee<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        fee_percentage: u8,
+    ) -> Result<()> {
+        verify_authority(&ctx.accounts.game_authority, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        require!(fee_percentage <= 10, ErrorCode::FeeTooHigh); // Max 10% fee
+
+        let game_authority = &mut ctx.accounts.game_authority;
+        let old_fee_percentage = game_authority.fee_percentage;
+        game_authority.fee_percentage = fee_percentage;
+
+        emit!(FeeUpdated {
+            old_fee_percentage,
+            fee_percentage,
+        });
+
+        Ok(())
+    }
+
+    /// Update the rake cap and how the rake is split between the protocol, table hosts and the
+    /// referral pool (authority only)
+    ///
+    /// The shares are in basis points and must add up to 10,000. A new cap applies to tables
+    /// created afterwards.
+    pub fn update_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        rake_cap_big_blinds: u8,
+        protocol_fee_bps: u16,
+        host_fee_bps: u16,
+        referral_fee_bps: u16,
+    ) -> Result<()> {
+        verify_authority(&ctx.accounts.game_authority, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let total_bps = protocol_fee_bps as u32 + host_fee_bps as u32 + referral_fee_bps as u32;
+        require!(total_bps == 10_000, ErrorCode::InvalidFeeSplit);
+
+        let game_authority = &mut ctx.accounts.game_authority;
+        game_authority.rake_cap_big_blinds = rake_cap_big_blinds;
+        game_authority.protocol_fee_bps = protocol_fee_bps;
+        game_authority.host_fee_bps = host_fee_bps;
+        game_authority.referral_fee_bps = referral_fee_bps;
+
+        emit!(ConfigUpdated {
+            rake_cap_big_blinds,
+            protocol_fee_bps,
+            host_fee_bps,
+            referral_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a new authority, who must accept before the handover takes effect
+    ///
+    /// Proposing the default pubkey cancels a pending handover.
+    pub fn propose_authority<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        verify_authority(&ctx.accounts.game_authority, &ctx.accounts.authority, ctx.remaining_accounts)?;
+
+        let game_authority = &mut ctx.accounts.game_authority;
+        game_authority.pending_authority = new_authority;
+
+        emit!(AuthorityProposed {
+            authority: game_authority.authority,
+            pending_authority: new_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Accept a proposed handover and become the authority
+    ///
+    /// Any signer set is cleared, so the new authority starts out acting alone.
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let game_authority = &mut ctx.accounts.game_authority;
+        let previous_authority = game_authority.authority;
+        game_authority.authority = ctx.accounts.new_authority.key();
+        game_authority.pending_authority = Pubkey::default();
+        game_authority.signers = Vec::new();
+        game_authority.threshold = 0;
+
+        emit!(AuthorityTransferred {
+            previous_authority,
+            authority: game_authority.authority,
+        });
+
+        Ok(())
+    }
+
+    /// Require `threshold` of `signers` to approve every authority action from now on
+    ///
+    /// An empty signer set with a threshold of 0 returns control to the authority alone.
+    pub fn set_authority_signers<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        verify_authority(&ctx.accounts.game_authority, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        require!(signers.len() <= MAX_AUTHORITY_SIGNERS, ErrorCode::InvalidSignerSet);
+        require!(
+            if signers.is_empty() { threshold == 0 } else { threshold > 0 && threshold as usize <= signers.len() },
+            ErrorCode::InvalidSignerSet
+        );
+        for (i, signer) in signers.iter().enumerate() {
+            require!(!signers[..i].contains(signer), ErrorCode::InvalidSignerSet);
+        }
+
+        let game_authority = &mut ctx.accounts.game_authority;
+        game_authority.signers = signers.clone();
+        game_authority.threshold = threshold;
+
+        emit!(AuthoritySignersUpdated { signers, threshold });
+
+        Ok(())
+    }
+
+    /// Replace the list of VRF oracle programs tables may deal with (authority only)
+    pub fn set_oracles<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        oracles: Vec<Pubkey>,
+    ) -> Result<()> {
+        verify_authority(&ctx.accounts.game_authority, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        require!(oracles.len() <= MAX_ORACLES, ErrorCode::TooManyOracles);
+
+        let game_authority = &mut ctx.accounts.game_authority;
+        game_authority.oracles = oracles.clone();
+
+        emit!(OraclesUpdated { oracles });
+
+        Ok(())
+    }
+
+    /// Replace the list of off-chain dealers tables may use (authority only)
+    pub fn set_dealers<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        dealers: Vec<Pubkey>,
+    ) -> Result<()> {
+        verify_authority(&ctx.accounts.game_authority, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        require!(dealers.len() <= MAX_DEALERS, ErrorCode::TooManyDealers);
+
+        let game_authority = &mut ctx.accounts.game_authority;
+        game_authority.dealers = dealers.clone();
+
+        emit!(DealersUpdated { dealers });
+
+        Ok(())
+    }
+
+    /// Pause or resume the parts of the game selected by `pause_scope` (authority only)
+    ///
+    /// `pause_scope` combines the `PAUSE_*` flags, and a pause must name at least one of them.
+    /// Leaving a table is never paused, so players can always withdraw their chips.
+    pub fn set_paused<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        paused: bool,
+        pause_scope: u8,
+    ) -> Result<()> {
+        verify_authority(&ctx.accounts.game_authority, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        validate_pause_scope(paused, pause_scope)?;
+
+        let game_authority = &mut ctx.accounts.game_authority;
+        game_authority.paused = paused;
+        game_authority.pause_scope = pause_scope;
+
+        emit!(PauseUpdated { paused, pause_scope });
+
+        Ok(())
+    }
+
+    /// Withdraw accumulated protocol or referral fees from the treasury (authority only)
+    pub fn withdraw_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawFees<'info>>,
+        bucket: FeeBucket,
+        amount: u64,
+    ) -> Result<()> {
+        verify_authority(&ctx.accounts.game_authority, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let game_authority = &mut ctx.accounts.game_authority;
+        
+        let balance = match bucket {
+            FeeBucket::Protocol => &mut game_authority.protocol_fees,
+            FeeBucket::Referral => &mut game_authority.referral_fees,
+        };
+        require!(amount > 0 && amount <= *balance, ErrorCode::InsufficientFees);
+        *balance = balance.checked_sub(amount).unwrap();
+        
+        let seeds = &[b"game_authority".as_ref(), &[game_authority.bump]];
+        let signer = &[&seeds[..]];
+        
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.treasury.to_account_info(),
+            to: ctx.accounts.destination.to_account_info(),
+            authority: ctx.accounts.game_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        Ok(())
+    }
+
     /// Create a new poker table with specified parameters
+    ///
+    /// Cash tables accept buy-ins between `min_buy_in` and `max_buy_in`, and `buy_in` is the
+    /// host's own. For a sit-and-go `buy_in` is the entry fee every player pays.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_table(
         ctx: Context<CreateTable>,
         table_id: String,
         buy_in: u64,
+        min_buy_in: u64,
+        max_buy_in: u64,
         small_blind: u64,
         big_blind: u64,
+        ante: u64,
         max_players: u8,
         is_private: bool,
+        vrf_oracle: Option<Pubkey>,
+        dealer: Pubkey,
+        encryption_key: [u8; 32],
+        betting_structure: BettingStructure,
+        game_variant: GameVariant,
+        tournament: Option<TournamentConfig>,
+        rebuys: RebuyConfig,
+        action_clock: ActionClockConfig,
     ) -> Result<()> {
-        require!(max_players >= 2 && max_players <= 9, ErrorCode::InvalidPlayerCount);
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_CREATE)?;
+        
+        // Tournaments start at the first level of their blind schedule
+        let (small_blind, big_blind, ante) = match tournament.as_ref().and_then(|config| config.blind_levels.first()) {
+            Some(level) => (level.small_blind, level.big_blind, level.ante),
+            None => (small_blind, big_blind, ante),
+        };
+        
+        require!(max_players >= 2 && max_players <= max_seats(&game_variant), ErrorCode::InvalidPlayerCount);
         require!(big_blind >= small_blind, ErrorCode::InvalidBlinds);
-        require!(buy_in >= big_blind * 10, ErrorCode::BuyInTooSmall);
+        require!(
+            game_variant != GameVariant::SevenCardStud || small_blind > 0,
+            ErrorCode::InvalidBlinds
+        ); // Stud uses the small blind as its bring-in
+        require!(big_blind > 0 || ante > 0, ErrorCode::InvalidBlinds); // Ante-only tables have no blinds
+        match &tournament {
+            // A tournament buy-in only funds the prize pool, so it need not cover the blinds
+            Some(config) => validate_tournament_config(config, max_players as usize, &game_variant)?,
+            None => {
+                require!(min_buy_in >= std::cmp::max(big_blind, ante) * 10, ErrorCode::BuyInTooSmall);
+                require!(max_buy_in >= min_buy_in, ErrorCode::InvalidBuyInRange);
+                require!(buy_in >= min_buy_in && buy_in <= max_buy_in, ErrorCode::InvalidBuyIn);
+                require!(
+                    rebuys.max_stack == 0 || rebuys.max_stack >= max_buy_in,
+                    ErrorCode::InvalidRebuyConfig
+                );
+            }
+        }
+        require!(action_clock.action_timeout > 0, ErrorCode::InvalidActionTimeout);
+        require!(
+            action_clock.time_bank >= 0 && action_clock.time_bank_refill >= 0,
+            ErrorCode::InvalidTimeBank
+        );
         require!(table_id.len() <= 32, ErrorCode::TableIdTooLong);
+        require!(encryption_key != [0; 32], ErrorCode::InvalidEncryptionKey);
+        // The dealer knows the deck order, so it must be an approved operator and not also a
+        // player at the table
+        require!(ctx.accounts.game_authority.dealers.contains(&dealer), ErrorCode::UnapprovedDealer);
+        require!(dealer != ctx.accounts.host.key(), ErrorCode::DealerCannotPlay);
+        if let Some(oracle) = vrf_oracle {
+            require!(ctx.accounts.game_authority.oracles.contains(&oracle), ErrorCode::UnapprovedOracle);
+        }
 
         let table = &mut ctx.accounts.table;
         table.host = ctx.accounts.host.key();
         table.table_id = table_id;
         table.buy_in = buy_in;
+        table.min_buy_in = if tournament.is_some() { buy_in } else { min_buy_in };
+        table.max_buy_in = if tournament.is_some() { buy_in } else { max_buy_in };
         table.small_blind = small_blind;
         table.big_blind = big_blind;
+        table.ante = ante;
         table.max_players = max_players;
         table.is_private = is_private;
+        table.betting_structure = betting_structure;
+        table.game_variant = game_variant;
+        table.vrf_oracle = vrf_oracle.unwrap_or_default();
+        table.dealer = dealer;
+        table.vault = ctx.accounts.table_vault.key();
         table.status = TableStatus::Waiting;
         table.pot = 0;
         table.current_player_index = 0;
         table.dealer_index = 0;
         table.round = Round::NotStarted;
         table.player_count = 0;
+        table.hands_played = 0;
+        table.max_stack = rebuys.max_stack;
+        table.max_rebuys = rebuys.max_rebuys;
+        table.rebuy_levels = rebuys.rebuy_levels;
+        table.add_on_chips = rebuys.add_on_chips;
+        table.action_timeout = action_clock.action_timeout;
+        table.sit_out_on_timeout = action_clock.sit_out_on_timeout;
+        table.time_bank = action_clock.time_bank;
+        table.time_bank_refill = action_clock.time_bank_refill;
+        table.time_bank_refill_hands = action_clock.time_bank_refill_hands;
+        match tournament {
+            Some(config) => {
+                table.game_mode = GameMode::SitAndGo;
+                table.starting_stack = config.starting_stack;
+                table.blind_levels = config.blind_levels;
+                table.level_clock = config.level_clock;
+                table.level_duration = config.level_duration;
+                table.payouts_bps = config.payouts_bps;
+                table.prize_pool = buy_in;
+            }
+            None => {
+                // Fees are only held in one mint, so tables playing for another token are not raked
+                table.game_mode = GameMode::CashGame;
+                if ctx.accounts.mint.key() == ctx.accounts.game_authority.fee_mint {
+                    table.rake_percentage = ctx.accounts.game_authority.fee_percentage;
+                    table.rake_cap_big_blinds = ctx.accounts.game_authority.rake_cap_big_blinds;
+                    table.host_fee_bps = ctx.accounts.game_authority.host_fee_bps;
+                }
+            }
+        }
         table.bump = *ctx.bumps.get("table").unwrap();
+        escrow_crank_bounty(&ctx.accounts.host, table, &ctx.accounts.system_program)?;
         
         // Initialize empty player slots
         table.players = vec![Pubkey::default(); max_players as usize];
@@ -59,6 +406,12 @@ pub mod poker_game {
         // Add host as first player
         table.players[0] = ctx.accounts.host.key();
         table.player_count = 1;
+        
+        // Tournament entrants all start with the same stack, whatever the buy-in
+        let starting_chips = match table.game_mode {
+            GameMode::SitAndGo | GameMode::MultiTable => table.starting_stack,
+            GameMode::CashGame => buy_in,
+        };
 
         // Transfer buy-in from host to table vault
         let cpi_accounts = Transfer {
@@ -74,11 +427,14 @@ pub mod poker_game {
         let player_state = &mut ctx.accounts.player_state;
         player_state.player = ctx.accounts.host.key();
         player_state.table = ctx.accounts.table.key();
-        player_state.chips = buy_in;
+        player_state.encryption_key = encryption_key;
+        player_state.chips = starting_chips;
         player_state.is_active = true;
         player_state.is_folded = false;
         player_state.current_bet = 0;
-        player_state.cards = [0, 0]; // Will be set when game starts
+        player_state.total_contributed = 0;
+        player_state.cards = [0; MAX_HOLE_CARDS]; // Will be set when revealed at showdown
+        player_state.time_bank = ctx.accounts.table.time_bank;
         player_state.bump = *ctx.bumps.get("player_state").unwrap();
 
         // Update game authority stats
@@ -89,12 +445,47 @@ pub mod poker_game {
     }
 
     /// Join an existing poker table
-    pub fn join_table(ctx: Context<JoinTable>) -> Result<()> {
+    ///
+    /// `encryption_key` is the player's X25519 public key; the dealer encrypts hole cards to it.
+    /// `amount` is the buy-in: within the table's range at cash tables, or exactly the entry
+    /// fee for a sit-and-go.
+    pub fn join_table(ctx: Context<JoinTable>, encryption_key: [u8; 32], amount: u64) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_JOIN)?;
+        let player_key = ctx.accounts.player.key();
         let table = &mut ctx.accounts.table;
         
+        require!(encryption_key != [0; 32], ErrorCode::InvalidEncryptionKey);
+        require!(player_key != table.dealer, ErrorCode::DealerCannotPlay);
+        
         // Validate table state
         require!(table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
         require!(table.player_count < table.max_players, ErrorCode::TableFull);
+        require!(table.reveal_deadline == 0, ErrorCode::ShuffleInProgress);
+        require!(table.pending_randomness == [0; 32], ErrorCode::RandomnessPending);
+        let registration_open = match table.game_mode {
+            GameMode::CashGame => true,
+            GameMode::SitAndGo => table.hands_played == 0,
+            GameMode::MultiTable => false, // Entrants are seated by `register_tournament`
+        };
+        require!(registration_open, ErrorCode::RegistrationClosed);
+        
+        // A cash player who left recently must come back with at least the stack they left with
+        let clock = Clock::get()?;
+        let (min_buy_in, max_buy_in) = match take_recent_departure(table, &player_key, clock.unix_timestamp) {
+            Some(stack) => (
+                std::cmp::max(table.min_buy_in, stack),
+                std::cmp::max(table.max_buy_in, stack),
+            ),
+            None => (table.min_buy_in, table.max_buy_in),
+        };
+        require!(amount >= min_buy_in && amount <= max_buy_in, ErrorCode::InvalidBuyIn);
+        
+        // Every seated player must be able to leave a departure behind, so the table fills no
+        // further while the unexpired departures leave no room for one more
+        require!(
+            table.recent_departures.len() + (table.player_count as usize) < MAX_RECENT_DEPARTURES,
+            ErrorCode::DepartureListFull
+        );
         
         // Find empty slot
         let mut slot_index = table.max_players as usize;
@@ -118,597 +509,4148 @@ pub mod poker_game {
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, table.buy_in)?;
+        token::transfer(cpi_ctx, amount)?;
+        
+        // A tournament buy-in goes to the prize pool in exchange for the starting stack
+        let starting_chips = match table.game_mode {
+            GameMode::SitAndGo => {
+                table.prize_pool = table.prize_pool.checked_add(amount).unwrap();
+                table.starting_stack
+            }
+            GameMode::CashGame => amount,
+            GameMode::MultiTable => return err!(ErrorCode::RegistrationClosed),
+        };
         
         // Create player state
         let player_state = &mut ctx.accounts.player_state;
         player_state.player = ctx.accounts.player.key();
         player_state.table = ctx.accounts.table.key();
-        player_state.chips = table.buy_in;
+        player_state.encryption_key = encryption_key;
+        player_state.chips = starting_chips;
         player_state.is_active = true;
         player_state.is_folded = false;
         player_state.current_bet = 0;
-        player_state.cards = [0, 0]; // Will be set when game starts
+        player_state.total_contributed = 0;
+        player_state.cards = [0; MAX_HOLE_CARDS]; // Will be set when revealed at showdown
+        player_state.time_bank = ctx.accounts.table.time_bank;
         player_state.bump = *ctx.bumps.get("player_state").unwrap();
         
         Ok(())
     }
 
-    /// Start a poker game on a table that has enough players
-    pub fn start_game(ctx: Context<StartGame>, seed: u64) -> Result<()> {
+    /// Dealer commits to the Merkle root of a secretly ordered deck for the next hand
+    ///
+    /// This must happen before the deck seed is known. The seed then decides which committed
+    /// positions each seat and the board receive, so the dealer cannot steer cards to anyone.
+    /// A dealer removed from the approved set deals no further hands.
+    pub fn commit_deck(ctx: Context<DealerAction>, deck_commitment: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.game_authority.dealers.contains(&ctx.accounts.dealer.key()),
+            ErrorCode::UnapprovedDealer
+        );
         let table = &mut ctx.accounts.table;
         
         // Validate table state
         require!(table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
-        require!(table.player_count >= 2, ErrorCode::NotEnoughPlayers);
-        require!(ctx.accounts.host.key() == table.host, ErrorCode::NotTableHost);
+        require!(table.deck_commitment == [0; 32], ErrorCode::DeckAlreadyCommitted);
+        require!(deck_commitment != [0; 32], ErrorCode::InvalidDeckCommitment);
+        require!(table.reveal_count == 0, ErrorCode::ShuffleInProgress);
+        require!(table.pending_randomness == [0; 32], ErrorCode::RandomnessPending);
+        
+        table.deck_commitment = deck_commitment;
         
-        // Update table status
-        table.status = TableStatus::Playing;
-        table.round = Round::PreFlop;
+        Ok(())
+    }
+
+    /// Dealer delivers a player's hole cards encrypted to the player's registered X25519 key
+    ///
+    /// The ciphertext carries each card and its salt; Merkle proofs are not secret and are
+    /// published by the dealer alongside it.
+    pub fn deliver_hole_cards(ctx: Context<DeliverHoleCards>, encrypted_cards: Vec<u8>) -> Result<()> {
+        let table = &ctx.accounts.table;
+        let player_state = &mut ctx.accounts.player_state;
         
-        // Set dealer position (can be randomized based on seed)
-        table.dealer_index = (seed % table.player_count as u64) as u8;
+        // Validate table and player state
+        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        require!(player_state.is_active && !player_state.is_folded, ErrorCode::PlayerNotActive);
+        require!(
+            encrypted_cards.len() <= MAX_ENCRYPTED_CARDS_LEN,
+            ErrorCode::EncryptedCardsTooLong
+        );
         
-        // Calculate small blind and big blind positions
-        let sb_index = (table.dealer_index + 1) % table.player_count;
-        let bb_index = (table.dealer_index + 2) % table.player_count;
+        player_state.encrypted_cards = encrypted_cards;
         
-        // Set current player to the one after big blind
-        table.current_player_index = (bb_index + 1) % table.player_count;
+        Ok(())
+    }
+
+    /// Dealer reveals the community cards of the current street (flop, turn or river)
+    ///
+    /// Each card is checked against the committed deck, so the board cannot change after the
+    /// deal and betting on a street only opens once its cards are public.
+    pub fn reveal_street(
+        ctx: Context<RevealStreet>,
+        cards: Vec<u8>,
+        salts: Vec<[u8; 32]>,
+        proofs: Vec<[[u8; 32]; DECK_TREE_DEPTH]>,
+    ) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
         
-        // Deal cards to players (in a real implementation, this would use a verifiable random function)
-        // For now, we'll use a simple deterministic approach based on the seed
-        let mut deck = generate_shuffled_deck(seed);
+        // Validate table state
+        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        let first = table.community_revealed as usize;
+        let end = board_cards_for_round(&table.round) as usize;
+        require!(end > first, ErrorCode::StreetAlreadyRevealed);
+        require!(
+            cards.len() == end - first && salts.len() == cards.len() && proofs.len() == cards.len(),
+            ErrorCode::InvalidStreetReveal
+        );
         
-        // Deal two cards to each active player
-        let mut card_index = 0;
-        for (i, player_pubkey) in table.players.iter().enumerate() {
-            if *player_pubkey != Pubkey::default() {
-                // Find player state account
-                let seeds = &[
-                    b"player_state".as_ref(),
-                    player_pubkey.as_ref(),
-                    table.key().as_ref(),
-                    &[ctx.accounts.player_states[i].bump],
-                ];
-                let player_state = &mut ctx.accounts.player_states[i];
-                
-                // Deal two cards to this player
-                player_state.cards = [deck[card_index], deck[card_index + 1]];
-                card_index += 2;
+        for (i, &card) in cards.iter().enumerate() {
+            let index = first + i;
+            let position = table.community_positions[index];
+            require!(
+                verify_deck_card(&table.deck_commitment, position, card, &salts[i], &proofs[i]),
+                ErrorCode::InvalidCardProof
+            );
+            
+            // A repeated or foreign card proves the committed deck was not a valid permutation
+            if table.community_cards[..index].contains(&card) || !deck_cards(&table.game_variant).contains(&card) {
+                table.misdeal = true;
             }
+            
+            table.community_cards[index] = card;
         }
+        table.community_revealed = end as u8;
         
-        // Store community cards for later reveals
-        table.community_cards = [
-            deck[card_index],     // flop 1
-            deck[card_index + 1], // flop 2
-            deck[card_index + 2], // flop 3
-            deck[card_index + 3], // turn
-            deck[card_index + 4], // river
-        ];
-        
-        // Post blinds
-        let sb_player = &mut ctx.accounts.player_states[sb_index as usize];
-        let bb_player = &mut ctx.accounts.player_states[bb_index as usize];
-        
-        // Small blind
-        sb_player.current_bet = table.small_blind;
-        sb_player.chips = sb_player.chips.checked_sub(table.small_blind).unwrap();
-        
-        // Big blind
-        bb_player.current_bet = table.big_blind;
-        bb_player.chips = bb_player.chips.checked_sub(table.big_blind).unwrap();
-        
-        // Update pot
-        table.pot = table.small_blind.checked_add(table.big_blind).unwrap();
+        // When everyone left is all-in there is no betting, so move straight to the next street
+        if betting_closed(table, &seats) {
+            advance_round(table, &mut seats)?;
+        }
         
-        // Initialize game state
-        table.highest_bet = table.big_blind;
+        // The first player's action clock starts once they can see the street
+        table.turn_started_at = Clock::get()?.unix_timestamp;
         
-        Ok(())
+        seats.save()
     }
 
-    /// Player makes a bet or raise
-    pub fn bet(ctx: Context<PlayerAction>, amount: u64) -> Result<()> {
+    /// Dealer reveals the face-up cards of the current stud street for every live player
+    ///
+    /// Cards are given in seat order, covering each player's upcards that are still face down.
+    /// On third street the lowest upcard then posts the bring-in; later streets are opened by
+    /// the best visible hand.
+    pub fn reveal_upcards(
+        ctx: Context<RevealStreet>,
+        cards: Vec<u8>,
+        salts: Vec<[u8; 32]>,
+        proofs: Vec<[[u8; 32]; DECK_TREE_DEPTH]>,
+    ) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
         let table = &mut ctx.accounts.table;
-        let player_state = &mut ctx.accounts.player_state;
         
-        // Validate table and player state
+        // Validate table state
         require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
-        require!(!player_state.is_folded, ErrorCode::PlayerFolded);
-        require!(player_state.is_active, ErrorCode::PlayerNotActive);
-        
-        // Verify it's this player's turn
-        let current_player_pubkey = table.players[table.current_player_index as usize];
-        require!(current_player_pubkey == ctx.accounts.player.key(), ErrorCode::NotPlayerTurn);
-        
-        // Calculate how much more the player needs to bet
-        let additional_bet = amount.checked_sub(player_state.current_bet).unwrap();
-        
-        // Verify player has enough chips
-        require!(player_state.chips >= additional_bet, ErrorCode::InsufficientChips);
         
-        // Verify bet is at least the minimum raise
-        if amount > table.highest_bet {
-            let min_raise = table.highest_bet.checked_add(table.big_blind).unwrap();
-            require!(amount >= min_raise, ErrorCode::BetTooSmall);
-        } else {
-            require!(amount == table.highest_bet, ErrorCode::BetTooSmall);
+        // Every live player receives the upcards of this street that are not yet public
+        let target = upcards_for_round(&table.round) as usize;
+        let mut pending = Vec::new();
+        let mut visible = Vec::new();
+        for (i, player_pubkey) in table.players.iter().enumerate() {
+            if *player_pubkey == Pubkey::default() {
+                continue;
+            }
+            
+            let player_state = &seats[i];
+            visible.extend_from_slice(&player_state.upcards[..player_state.upcards_revealed as usize]);
+            if player_state.is_active && !player_state.is_folded {
+                for upcard in player_state.upcards_revealed as usize..target {
+                    pending.push((i, upcard));
+                }
+            }
         }
+        require!(!pending.is_empty(), ErrorCode::StreetAlreadyRevealed);
+        require!(
+            cards.len() == pending.len() && salts.len() == cards.len() && proofs.len() == cards.len(),
+            ErrorCode::InvalidStreetReveal
+        );
         
-        // Update player state
-        player_state.chips = player_state.chips.checked_sub(additional_bet).unwrap();
-        player_state.current_bet = amount;
-        
-        // Update table state
-        table.pot = table.pot.checked_add(additional_bet).unwrap();
-        if amount > table.highest_bet {
-            table.highest_bet = amount;
+        let valid_cards = deck_cards(&table.game_variant);
+        for (j, &(seat, upcard)) in pending.iter().enumerate() {
+            let player_state = &mut seats[seat];
+            let position = player_state.hole_positions[STUD_DOWN_CARDS + upcard];
+            require!(
+                verify_deck_card(&table.deck_commitment, position, cards[j], &salts[j], &proofs[j]),
+                ErrorCode::InvalidCardProof
+            );
+            
+            // A repeated or foreign card proves the committed deck was not a valid permutation
+            if visible.contains(&cards[j]) || !valid_cards.contains(&cards[j]) {
+                table.misdeal = true;
+            }
+            visible.push(cards[j]);
+            
+            player_state.upcards[upcard] = cards[j];
+            player_state.upcards_revealed = (upcard + 1) as u8;
         }
         
-        // Move to next player
-        advance_to_next_player(table)?;
+        // When everyone left is all-in there is no betting, so move straight to the next street
+        if betting_closed(table, &seats) {
+            advance_round(table, &mut seats)?;
+        } else if table.round == Round::ThirdStreet {
+            post_bring_in(table, &mut seats);
+        } else if let Some(seat) = stud_opener(table, &seats) {
+            table.current_player_index = seat as u8;
+        }
         
-        // Check if round is complete
-        check_round_completion(ctx)?;
+        // The first player's action clock starts once they can see the street
+        table.turn_started_at = Clock::get()?.unix_timestamp;
         
-        Ok(())
+        seats.save()
     }
 
-    /// Player checks (bet 0 when no previous bets)
-    pub fn check(ctx: Context<PlayerAction>) -> Result<()> {
+    /// Player opens their hole cards at showdown against the dealer's deck commitment
+    pub fn reveal_hole_cards(
+        ctx: Context<RevealHoleCards>,
+        cards: Vec<u8>,
+        salts: Vec<[u8; 32]>,
+        proofs: Vec<[[u8; 32]; DECK_TREE_DEPTH]>,
+    ) -> Result<()> {
         let table = &mut ctx.accounts.table;
         let player_state = &mut ctx.accounts.player_state;
         
         // Validate table and player state
         require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        require!(table.round == Round::Showdown, ErrorCode::NotShowdownRound);
         require!(!player_state.is_folded, ErrorCode::PlayerFolded);
         require!(player_state.is_active, ErrorCode::PlayerNotActive);
+        require!(!player_state.cards_revealed, ErrorCode::CardsAlreadyRevealed);
         
-        // Verify it's this player's turn
-        let current_player_pubkey = table.players[table.current_player_index as usize];
-        require!(current_player_pubkey == ctx.accounts.player.key(), ErrorCode::NotPlayerTurn);
+        let hole_cards = hole_card_count(&table.game_variant);
+        require!(
+            cards.len() == hole_cards && salts.len() == hole_cards && proofs.len() == hole_cards,
+            ErrorCode::InvalidHoleCardReveal
+        );
         
-        // Can only check if no one has bet or player has matched the highest bet
-        require!(table.highest_bet == 0 || player_state.current_bet == table.highest_bet, ErrorCode::CannotCheck);
+        for i in 0..hole_cards {
+            require!(
+                verify_deck_card(
+                    &table.deck_commitment,
+                    player_state.hole_positions[i],
+                    cards[i],
+                    &salts[i],
+                    &proofs[i],
+                ),
+                ErrorCode::InvalidCardProof
+            );
+        }
         
-        // Move to next player
-        advance_to_next_player(table)?;
+        // Duplicated cards prove the dealer committed an invalid deck; repeats across hands
+        // are caught at showdown
+        let revealed_board = &table.community_cards[..table.community_revealed as usize];
+        let has_duplicate = has_repeated_card(&cards);
+        let valid_cards = deck_cards(&table.game_variant);
+        let has_foreign = cards.iter().any(|card| !valid_cards.contains(card));
+        if has_duplicate || has_foreign || revealed_board.iter().any(|card| cards.contains(card)) {
+            table.misdeal = true;
+        }
         
-        // Check if round is complete
-        check_round_completion(ctx)?;
+        player_state.cards[..hole_cards].copy_from_slice(&cards);
+        player_state.cards_revealed = true;
         
         Ok(())
     }
 
-    /// Player calls the current highest bet
-    pub fn call(ctx: Context<PlayerAction>) -> Result<()> {
+    /// Submit a commitment to this player's shuffle secret for the next hand
+    pub fn commit_shuffle(ctx: Context<CommitShuffle>, commitment: [u8; 32]) -> Result<()> {
         let table = &mut ctx.accounts.table;
         let player_state = &mut ctx.accounts.player_state;
         
         // Validate table and player state
-        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
-        require!(!player_state.is_folded, ErrorCode::PlayerFolded);
+        require!(table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
+        require!(table.vrf_oracle == Pubkey::default(), ErrorCode::TableUsesVrf);
+        require!(table.reveal_deadline == 0, ErrorCode::RevealWindowOpen);
         require!(player_state.is_active, ErrorCode::PlayerNotActive);
+        require!(player_state.shuffle_commitment == [0; 32], ErrorCode::AlreadyCommitted);
+        require!(commitment != [0; 32], ErrorCode::InvalidShuffleCommitment);
         
-        // Verify it's this player's turn
-        let current_player_pubkey = table.players[table.current_player_index as usize];
-        require!(current_player_pubkey == ctx.accounts.player.key(), ErrorCode::NotPlayerTurn);
-        
-        // Calculate call amount
-        let call_amount = table.highest_bet.checked_sub(player_state.current_bet).unwrap();
-        
-        // Handle all-in if player doesn't have enough chips
-        let actual_call = std::cmp::min(call_amount, player_state.chips);
-        
-        // Update player state
-        player_state.chips = player_state.chips.checked_sub(actual_call).unwrap();
-        player_state.current_bet = player_state.current_bet.checked_add(actual_call).unwrap();
-        
-        // If player couldn't match the full bet, they're all-in
-        if actual_call < call_amount {
-            player_state.is_all_in = true;
+        // The first commitment starts the clock for everyone else to commit
+        let clock = Clock::get()?;
+        if table.commit_count == 0 {
+            table.commit_deadline = clock.unix_timestamp.checked_add(SHUFFLE_COMMIT_WINDOW).unwrap();
         }
         
-        // Update table state
-        table.pot = table.pot.checked_add(actual_call).unwrap();
-        
-        // Move to next player
-        advance_to_next_player(table)?;
+        player_state.shuffle_commitment = commitment;
+        table.commit_count = table.commit_count.checked_add(1).unwrap();
         
-        // Check if round is complete
-        check_round_completion(ctx)?;
+        // Once every seated player has committed, open the reveal window
+        if table.commit_count == table.player_count {
+            let slot_hashes_data = ctx.accounts.recent_slothashes.try_borrow_data()?;
+            open_reveal_window(table, &slot_hashes_data[16..48], clock.unix_timestamp);
+        }
         
         Ok(())
     }
 
-    /// Player folds their hand
-    pub fn fold(ctx: Context<PlayerAction>) -> Result<()> {
+    /// Reveal the secret behind this player's shuffle commitment and mix it into the deck seed
+    pub fn reveal_shuffle(ctx: Context<ShuffleAction>, secret: [u8; 32]) -> Result<()> {
         let table = &mut ctx.accounts.table;
         let player_state = &mut ctx.accounts.player_state;
         
-        // Validate table and player state
-        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
-        require!(!player_state.is_folded, ErrorCode::PlayerFolded);
-        require!(player_state.is_active, ErrorCode::PlayerNotActive);
-        
-        // Verify it's this player's turn
-        let current_player_pubkey = table.players[table.current_player_index as usize];
-        require!(current_player_pubkey == ctx.accounts.player.key(), ErrorCode::NotPlayerTurn);
-        
-        // Update player state
-        player_state.is_folded = true;
-        
-        // Move to next player
-        advance_to_next_player(table)?;
+        // Reveals are only accepted once everyone has committed and before the deadline
+        require!(table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
+        require!(table.reveal_deadline != 0, ErrorCode::ShuffleNotCommitted);
+        require!(table.deck_commitment != [0; 32], ErrorCode::DeckNotCommitted);
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp <= table.reveal_deadline, ErrorCode::RevealWindowClosed);
+        require!(player_state.shuffle_commitment != [0; 32], ErrorCode::ShuffleNotCommitted);
+        require!(!player_state.has_revealed, ErrorCode::AlreadyRevealed);
         
-        // Check if only one player remains
-        let active_players = count_active_players(ctx);
-        if active_players == 1 {
-            // Find the winner and award the pot
-            for player_state in ctx.accounts.player_states.iter_mut() {
-                if player_state.is_active && !player_state.is_folded {
-                    player_state.chips = player_state.chips.checked_add(table.pot).unwrap();
-                    break;
-                }
-            }
-            
-            // End the game
-            table.status = TableStatus::Finished;
-            return Ok(());
-        }
+        // The commitment binds the secret to the player so it cannot be copied by another seat
+        let expected = hashv(&[&secret, ctx.accounts.player.key().as_ref()]).to_bytes();
+        require!(expected == player_state.shuffle_commitment, ErrorCode::InvalidShuffleReveal);
         
-        // Check if round is complete
-        check_round_completion(ctx)?;
+        player_state.has_revealed = true;
+        table.shuffle_entropy = hashv(&[&table.shuffle_entropy, &secret]).to_bytes();
+        table.reveal_count = table.reveal_count.checked_add(1).unwrap();
         
         Ok(())
     }
 
-    /// Determine winner and distribute pot at showdown
-    pub fn showdown(ctx: Context<Showdown>) -> Result<()> {
+    /// Sit out players who did not commit before the commit window closed
+    ///
+    /// Anyone may call this once the window has passed. The players who did commit go on to
+    /// reveal for this hand; if fewer than two committed, the commit phase starts over.
+    pub fn forfeit_uncommitted(ctx: Context<ForfeitUncommitted>) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
         let table = &mut ctx.accounts.table;
         
         // Validate table state
-        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
-        require!(table.round == Round::Showdown, ErrorCode::NotShowdownRound);
+        require!(table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
+        require!(table.reveal_deadline == 0, ErrorCode::RevealWindowOpen);
+        require!(table.commit_deadline != 0, ErrorCode::ShuffleNotCommitted);
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp > table.commit_deadline, ErrorCode::CommitWindowOpen);
         
-        // Calculate hand strengths for all active players
-        let mut best_hand_value = 0;
-        let mut winners = Vec::new();
+        if table.commit_count < 2 {
+            for player_state in seats.iter_mut() {
+                player_state.shuffle_commitment = [0; 32];
+            }
+            table.commit_count = 0;
+            table.commit_deadline = 0;
+            return seats.save();
+        }
         
         for (i, player_pubkey) in table.players.iter().enumerate() {
             if *player_pubkey == Pubkey::default() {
                 continue;
             }
             
-            let player_state = &ctx.accounts.player_states[i];
-            if player_state.is_folded || !player_state.is_active {
-                continue;
+            let player_state = &mut seats[i];
+            if player_state.shuffle_commitment == [0; 32] {
+                player_state.is_sitting_out = true;
+                if table.sit_out_on_timeout {
+                    player_state.is_away = true;
+                }
             }
-            
-            // Combine player's hole cards with community cards
-            let mut cards = Vec::with_capacity(7);
-            cards.push(player_state.cards[0]);
-            cards.push(player_state.cards[1]);
-            for &card in table.community_cards.iter() {
-                cards.push(card);
-            }
-            
-            // Evaluate hand strength
-            let hand_value = evaluate_poker_hand(&cards);
-            
-            if hand_value > best_hand_value {
-                best_hand_value = hand_value;
-                winners.clear();
-                winners.push(i);
-            } else if hand_value == best_hand_value {
-                winners.push(i);
-            }
-        }
-        
-        // Distribute pot among winners
-        let winner_share = table.pot / winners.len() as u64;
-        for &winner_index in winners.iter() {
-            let winner_state = &mut ctx.accounts.player_states[winner_index];
-            winner_state.chips = winner_state.chips.checked_add(winner_share).unwrap();
-        }
-        
-        // Handle remainder chips (give to first winner)
-        let remainder = table.pot % winners.len() as u64;
-        if remainder > 0 {
-            let first_winner = &mut ctx.accounts.player_states[winners[0]];
-            first_winner.chips = first_winner.chips.checked_add(remainder).unwrap();
         }
         
-        // End the game
-        table.status = TableStatus::Finished;
+        let slot_hashes_data = ctx.accounts.recent_slothashes.try_borrow_data()?;
+        open_reveal_window(table, &slot_hashes_data[16..48], clock.unix_timestamp);
         
-        Ok(())
+        seats.save()
     }
 
-    /// Reset the table for a new game
-    pub fn reset_table(ctx: Context<ResetTable>) -> Result<()> {
+    /// Penalize players who committed but did not reveal before the deadline
+    ///
+    /// Anyone may call this once the reveal window has closed. Each offender forfeits up to one
+    /// big blind, shared between the players who did reveal, and sits out the next hand.
+    pub fn forfeit_unrevealed(ctx: Context<ForfeitUnrevealed>) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
         let table = &mut ctx.accounts.table;
         
         // Validate table state
-        require!(table.status == TableStatus::Finished, ErrorCode::GameNotFinished);
-        require!(ctx.accounts.host.key() == table.host, ErrorCode::NotTableHost);
+        require!(table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
+        require!(table.reveal_deadline != 0, ErrorCode::ShuffleNotCommitted);
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp > table.reveal_deadline, ErrorCode::RevealWindowOpen);
         
-        // Reset table state
-        table.status = TableStatus::Waiting;
-        table.pot = 0;
-        table.round = Round::NotStarted;
-        table.highest_bet = 0;
+        // With nobody to compensate, start the commit phase over without penalties
+        if table.reveal_count == 0 {
+            for player_state in seats.iter_mut() {
+                player_state.shuffle_commitment = [0; 32];
+            }
+            table.commit_count = 0;
+            table.commit_deadline = 0;
+            table.reveal_deadline = 0;
+            table.shuffle_entropy = [0; 32];
+            return seats.save();
+        }
         
-        // Reset player states
-        for player_state in ctx.accounts.player_states.iter_mut() {
-            if player_state.is_active {
-                player_state.is_folded = false;
-                player_state.current_bet = 0;
-                player_state.is_all_in = false;
+        // Collect penalties from every player who failed to reveal
+        let mut penalties: u64 = 0;
+        let mut revealers = Vec::new();
+        for (i, player_pubkey) in table.players.iter().enumerate() {
+            if *player_pubkey == Pubkey::default() {
+                continue;
+            }
+            
+            let player_state = &mut seats[i];
+            if player_state.has_revealed {
+                revealers.push(i);
+            } else if !player_state.is_sitting_out {
+                let penalty = std::cmp::min(betting_unit(table), player_state.chips);
+                player_state.chips = player_state.chips.checked_sub(penalty).unwrap();
+                player_state.is_sitting_out = true;
+                penalties = penalties.checked_add(penalty).unwrap();
             }
         }
         
-        Ok(())
+        // Share the forfeited chips between the players who revealed
+        for (seat, share) in split_pot(penalties, &revealers, table.dealer_index, table.max_players) {
+            let player_state = &mut seats[seat];
+            player_state.chips = player_state.chips.checked_add(share).unwrap();
+        }
+        
+        seats.save()
     }
 
-    /// Leave a table and withdraw chips
-    pub fn leave_table(ctx: Context<LeaveTable>) -> Result<()> {
+    /// Start a poker game on a table that has enough players
+    ///
+    /// The deck seed is derived from every participant's revealed shuffle secret together with
+    /// the slot hash fixed when the reveal window opened, so no single party can choose the deal
+    /// and the host cannot pick a favourable moment to start. At tournament tables anyone may
+    /// start once the host has let `HOST_GRACE_PERIOD` pass after the reveal window.
+    pub fn start_game(ctx: Context<StartGame>) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_START)?;
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
         let table = &mut ctx.accounts.table;
-        let player_state = &mut ctx.accounts.player_state;
         
         // Validate table state
+        require!(table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
+        require!(table.player_count >= 2, ErrorCode::NotEnoughPlayers);
         require!(
-            table.status == TableStatus::Waiting || table.status == TableStatus::Finished,
-            ErrorCode::CannotLeaveActiveGame
+            table.game_mode != GameMode::SitAndGo || table.hands_played > 0 || table.player_count == table.max_players,
+            ErrorCode::TournamentNotFull
+        ); // A sit-and-go starts once every seat is taken
+        require!(
+            table.game_mode != GameMode::MultiTable || table.level_started_at != 0,
+            ErrorCode::TournamentNotStarted
         );
+        require!(table.vrf_oracle == Pubkey::default(), ErrorCode::TableUsesVrf);
+        require!(table.reveal_deadline != 0, ErrorCode::ShuffleNotCommitted);
+        let now = Clock::get()?.unix_timestamp;
+        require_host_or_expired(table, &ctx.accounts.caller.key(), table.reveal_deadline, now)?;
+        require!(table.deck_commitment != [0; 32], ErrorCode::DeckNotCommitted);
         
-        // Find player's index in the table
-        let mut player_index = table.max_players as usize;
+        // Every participant must have revealed; players who refused are sitting out this hand
+        let mut participants = 0;
         for (i, player_pubkey) in table.players.iter().enumerate() {
-            if *player_pubkey == ctx.accounts.player.key() {
-                player_index = i;
-                break;
+            if *player_pubkey == Pubkey::default() {
+                continue;
+            }
+            
+            let player_state = &seats[i];
+            if player_state.is_sitting_out {
+                continue;
             }
+            require!(player_state.has_revealed, ErrorCode::ShuffleIncomplete);
+            participants += 1;
         }
-        require!(player_index < table.max_players as usize, ErrorCode::PlayerNotAtTable);
+        require!(participants >= 2, ErrorCode::NotEnoughPlayers);
         
-        // Remove player from table
-        table.players[player_index] = Pubkey::default();
-        table.player_count = table.player_count.checked_sub(1).unwrap();
+        // The entropy already holds the slot hash and every revealed secret; mixing in the deck
+        // commitment ties the seed to the order the dealer committed to before any reveal
+        let seed_bytes = hashv(&[&table.shuffle_entropy, &table.deck_commitment]).to_bytes();
+        let seed = u64::from_le_bytes(seed_bytes[..8].try_into().unwrap());
         
-        // Transfer chips from table vault to player
+        deal_hand(table, &mut seats, seed)?;
+        
+        seats.save()
+    }
+
+    /// Ask the table's VRF oracle for randomness to deal the next hand
+    ///
+    /// Dealing happens in `fulfill_randomness` once the oracle delivers a verified result. At
+    /// tournament tables anyone may request once the host has let `HOST_GRACE_PERIOD` pass
+    /// after the last hand finished.
+    pub fn request_randomness(ctx: Context<RequestRandomness>) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_START)?;
+        let table_key = ctx.accounts.table.key();
+        let table = &mut ctx.accounts.table;
+        
+        // Validate table state
+        require!(table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
+        require!(table.player_count >= 2, ErrorCode::NotEnoughPlayers);
+        require!(
+            table.game_mode != GameMode::SitAndGo || table.hands_played > 0 || table.player_count == table.max_players,
+            ErrorCode::TournamentNotFull
+        ); // A sit-and-go starts once every seat is taken
+        require!(
+            table.game_mode != GameMode::MultiTable || table.level_started_at != 0,
+            ErrorCode::TournamentNotStarted
+        );
+        require!(table.vrf_oracle != Pubkey::default(), ErrorCode::TableDoesNotUseVrf);
+        require!(
+            ctx.accounts.game_authority.oracles.contains(&table.vrf_oracle),
+            ErrorCode::UnapprovedOracle
+        );
+        require!(table.pending_randomness == [0; 32], ErrorCode::RandomnessPending);
+        require!(table.deck_commitment != [0; 32], ErrorCode::DeckNotCommitted);
+        let now = Clock::get()?.unix_timestamp;
+        require_host_or_expired(table, &ctx.accounts.caller.key(), table.finished_at, now)?;
+        
+        // Each request gets a unique id so a stale fulfilment can never deal a later hand. The id
+        // is the VRF input and covers the deck commitment, so an oracle cannot work out the
+        // randomness before the dealer has fixed the deck order
+        table.randomness_nonce = table.randomness_nonce.checked_add(1).unwrap();
+        let request_id = hashv(&[
+            table_key.as_ref(),
+            &table.randomness_nonce.to_le_bytes(),
+            &table.deck_commitment,
+        ])
+        .to_bytes();
+        table.pending_randomness = request_id;
+        table.randomness_requested_at = now;
+        
+        // The table PDA signs the request so the oracle knows which account to call back
+        let table_id = table.table_id.clone();
+        let bump = table.bump;
         let seeds = &[
             b"table".as_ref(),
-            table.table_id.as_bytes(),
-            &[table.bump],
+            table_id.as_bytes(),
+            &[bump],
         ];
         let signer = &[&seeds[..]];
         
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.table_vault.to_account_info(),
-            to: ctx.accounts.player_token_account.to_account_info(),
-            authority: ctx.accounts.table.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, player_state.chips)?;
+        let ix = vrf_oracle::request_randomness_ix(
+            &ctx.accounts.oracle_program.key(),
+            &table_key,
+            &ctx.accounts.oracle_state.key(),
+            request_id,
+        );
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.table.to_account_info(),
+                ctx.accounts.oracle_state.to_account_info(),
+                ctx.accounts.oracle_program.to_account_info(),
+            ],
+            signer,
+        )?;
         
-        // Mark player as inactive
-        player_state.is_active = false;
-        player_state.chips = 0;
+        Ok(())
+    }
+
+    /// Callback invoked by the VRF oracle once it has published its result; deals the hand
+    ///
+    /// The randomness is read from the oracle's result account rather than taken from the
+    /// caller. Its VRF proof is not verified on-chain, so the deal relies on trusting the
+    /// approved oracle program that signs the fulfilment and owns the result account.
+    pub fn fulfill_randomness(ctx: Context<FulfillRandomness>, request_id: [u8; 32]) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_START)?;
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
         
-        // If host is leaving and other players remain, transfer host status
-        if ctx.accounts.player.key() == table.host && table.player_count > 0 {
-            // Find first active player to be new host
-            for player_pubkey in table.players.iter() {
-                if *player_pubkey != Pubkey::default() && *player_pubkey != ctx.accounts.player.key() {
-                    table.host = *player_pubkey;
-                    break;
-                }
-            }
-        }
+        // Only the oracle program can sign for its authority PDA
+        require!(table.vrf_oracle != Pubkey::default(), ErrorCode::TableDoesNotUseVrf);
+        require!(
+            ctx.accounts.oracle_authority.key() == vrf_oracle::oracle_authority(&table.vrf_oracle),
+            ErrorCode::InvalidOracleAuthority
+        );
+        require!(table.pending_randomness != [0; 32], ErrorCode::NoRandomnessRequested);
+        require!(request_id == table.pending_randomness, ErrorCode::RandomnessRequestMismatch);
+        require!(table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
+        require!(table.player_count >= 2, ErrorCode::NotEnoughPlayers);
         
-        // If no players left, close the table
-        if table.player_count == 0 {
-            // In a real implementation, we would close the table account here
-            // and return the rent to the host
-        }
+        let result_data = ctx.accounts.oracle_result.try_borrow_data()?;
+        let (requester, result_id, randomness) =
+            vrf_oracle::read_result(&result_data).ok_or(ErrorCode::InvalidOracleResult)?;
+        require!(
+            requester == table.key() && result_id == request_id,
+            ErrorCode::RandomnessRequestMismatch
+        );
         
-        Ok(())
+        table.pending_randomness = [0; 32];
+        
+        let seed = u64::from_le_bytes(randomness[..8].try_into().unwrap());
+        deal_hand(table, &mut seats, seed)?;
+        
+        seats.save()
     }
-}
 
-/// Helper function to advance to the next active player
-fn advance_to_next_player(table: &mut Table) -> Result<()> {
-    let start_index = table.current_player_index;
-    loop {
-        table.current_player_index = (table.current_player_index + 1) % table.player_count;
+    /// Drop an oracle request that was never fulfilled
+    ///
+    /// Anyone may call this once `RANDOMNESS_REQUEST_TIMEOUT` has passed since the request. The
+    /// table can then request again; a late fulfilment of the dropped request is rejected, as
+    /// its id no longer matches.
+    pub fn cancel_randomness(ctx: Context<CancelRandomness>) -> Result<()> {
+        let table = &mut ctx.accounts.table;
         
-        // If we've gone all the way around, break
-        if table.current_player_index == start_index {
-            break;
-        }
+        // Validate table state
+        require!(table.pending_randomness != [0; 32], ErrorCode::NoRandomnessRequested);
+        let clock = Clock::get()?;
+        let expires_at = table.randomness_requested_at.checked_add(RANDOMNESS_REQUEST_TIMEOUT).unwrap();
+        require!(clock.unix_timestamp > expires_at, ErrorCode::RandomnessRequestActive);
         
-        // If we found an active player who hasn't folded, break
-        let player_pubkey = table.players[table.current_player_index as usize];
-        if player_pubkey != Pubkey::default() {
-            // In a real implementation, we would check if the player is active and hasn't folded
-            break;
-        }
+        table.pending_randomness = [0; 32];
+        
+        Ok(())
     }
-    
-    Ok(())
-}
 
-/// Helper function to check if the current betting round is complete
-fn check_round_completion(ctx: Context<PlayerAction>) -> Result<()> {
-    let table = &mut ctx.accounts.table;
-    
-    // Check if all active players have matched the highest bet or folded
-    let mut round_complete = true;
-    for (i, player_pubkey) in table.players.iter().enumerate() {
-        if *player_pubkey == Pubkey::default() {
-            continue;
+    /// Player makes a bet or raise
+    pub fn bet(ctx: Context<PlayerAction>, amount: u64) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
+        
+        // Validate table state
+        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        require!(street_revealed(table, &seats), ErrorCode::StreetNotRevealed);
+        require!(table.round != Round::Draw, ErrorCode::DrawInProgress);
+        
+        // Verify it's this player's turn; their seat's state is the one the round checks read
+        let seat = table.current_player_index as usize;
+        require!(table.players[seat] == ctx.accounts.player.key(), ErrorCode::NotPlayerTurn);
+        let player_state = &mut seats[seat];
+        require!(!player_state.is_folded, ErrorCode::PlayerFolded);
+        require!(player_state.is_active, ErrorCode::PlayerNotActive);
+        
+        // Time taken past the action window comes out of the player's time bank
+        let now = Clock::get()?.unix_timestamp;
+        charge_time_bank(table, player_state, now);
+        
+        // Calculate how much more the player needs to bet
+        let additional_bet = amount.checked_sub(player_state.current_bet).unwrap();
+        
+        // Verify player has enough chips
+        require!(player_state.chips >= additional_bet, ErrorCode::InsufficientChips);
+        let is_all_in = additional_bet == player_state.chips;
+        
+        // A raise must be at least the previous raise increment unless the player is all-in,
+        // and only a full raise reopens betting to players who already acted. Until a full bet
+        // is made (a stud bring-in or a short all-in), the first raise completes it to a full bet
+        let raise_from = if table.bets_this_street == 0 { 0 } else { table.highest_bet };
+        let raise_size = amount.saturating_sub(raise_from);
+        let is_full_raise = raise_size >= table.last_raise_size;
+        if amount > table.highest_bet {
+            require!(is_full_raise || is_all_in, ErrorCode::BetTooSmall);
+            require!(betting_reopened(table, player_state), ErrorCode::BettingNotReopened);
+            
+            // Enforce the table's betting structure on the size of the raise
+            match table.betting_structure {
+                BettingStructure::NoLimit => {}
+                BettingStructure::PotLimit => {
+                    // The largest raise is the size of the pot after calling
+                    let call_amount = table.highest_bet.checked_sub(player_state.current_bet).unwrap();
+                    let max_raise = table.pot.checked_add(call_amount).unwrap();
+                    require!(amount - table.highest_bet <= max_raise, ErrorCode::BetTooLarge);
+                }
+                BettingStructure::FixedLimit => {
+                    require!(raise_size <= table.last_raise_size, ErrorCode::BetTooLarge);
+                    require!(table.bets_this_street < FIXED_LIMIT_RAISE_CAP, ErrorCode::RaiseCapReached);
+                }
+            }
+        } else {
+            require!(amount == table.highest_bet, ErrorCode::BetTooSmall);
         }
         
-        let player_state = &ctx.accounts.player_states[i];
-        if player_state.is_folded || !player_state.is_active || player_state.is_all_in {
-            continue;
+        // Update player state
+        player_state.chips = player_state.chips.checked_sub(additional_bet).unwrap();
+        player_state.current_bet = amount;
+        player_state.total_contributed = player_state.total_contributed.checked_add(additional_bet).unwrap();
+        if player_state.chips == 0 {
+            player_state.is_all_in = true;
         }
         
-        if player_state.current_bet < table.highest_bet {
-            round_complete = false;
-            break;
+        player_state.has_acted = true;
+        player_state.last_action_bet = std::cmp::max(amount, table.highest_bet);
+        
+        // Update table state
+        table.pot = table.pot.checked_add(additional_bet).unwrap();
+        if amount > table.highest_bet {
+            record_raise(table, &mut seats, seat, amount, raise_size);
         }
+        
+        // Move to next player
+        advance_to_next_player(table, &seats)?;
+        table.turn_started_at = now;
+        
+        // Check if round is complete
+        check_round_completion(table, &mut seats)?;
+        
+        seats.save()
     }
-    
-    if round_complete {
-        // Reset bets for next round
-        for player_state in ctx.accounts.player_states.iter_mut() {
-            player_state.current_bet = 0;
-        }
+
+    /// Player checks (bet 0 when no previous bets)
+    pub fn check(ctx: Context<PlayerAction>) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
         
-        table.highest_bet = 0;
+        // Validate table state
+        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        require!(street_revealed(table, &seats), ErrorCode::StreetNotRevealed);
+        require!(table.round != Round::Draw, ErrorCode::DrawInProgress);
         
-        // Advance to next round
-        match table.round {
-            Round::PreFlop => {
-                table.round = Round::Flop;
-                // In a real implementation, we would reveal the flop cards here
-            }
-            Round::Flop => {
-                table.round = Round::Turn;
-                // In a real implementation, we would reveal the turn card here
-            }
-            Round::Turn => {
-                table.round = Round::River;
-                // In a real implementation, we would reveal the river card here
-            }
-            Round::River => {
-                table.round = Round::Showdown;
-                // In a real implementation, we would trigger showdown here
-            }
-            _ => {}
-        }
+        // Verify it's this player's turn; their seat's state is the one the round checks read
+        let seat = table.current_player_index as usize;
+        require!(table.players[seat] == ctx.accounts.player.key(), ErrorCode::NotPlayerTurn);
+        let player_state = &mut seats[seat];
+        require!(!player_state.is_folded, ErrorCode::PlayerFolded);
+        require!(player_state.is_active, ErrorCode::PlayerNotActive);
+        
+        // Time taken past the action window comes out of the player's time bank
+        let now = Clock::get()?.unix_timestamp;
+        charge_time_bank(table, player_state, now);
+        
+        // Can only check if no one has bet or player has matched the highest bet
+        require!(table.highest_bet == 0 || player_state.current_bet == table.highest_bet, ErrorCode::CannotCheck);
         
-        // Set current player to the one after the dealer
-        table.current_player_index = (table.dealer_index + 1) % table.player_count;
+        player_state.has_acted = true;
+        player_state.last_action_bet = table.highest_bet;
+        
+        // Move to next player
+        advance_to_next_player(table, &seats)?;
+        table.turn_started_at = now;
+        
+        // Check if round is complete
+        check_round_completion(table, &mut seats)?;
+        
+        seats.save()
     }
-    
-    Ok(())
-}
 
-/// Helper function to count active players who haven't folded
-fn count_active_players(ctx: Context<PlayerAction>) -> usize {
-    let table = &ctx.accounts.table;
-    let mut count = 0;
-    
-    for (i, player_pubkey) in table.players.iter().enumerate() {
-        if *player_pubkey == Pubkey::default() {
-            continue;
-        }
+    /// Player calls the current highest bet
+    pub fn call(ctx: Context<PlayerAction>) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
         
-        let player_state = &ctx.accounts.player_states[i];
-        if !player_state.is_folded && player_state.is_active {
-            count += 1;
+        // Validate table state
+        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        require!(street_revealed(table, &seats), ErrorCode::StreetNotRevealed);
+        require!(table.round != Round::Draw, ErrorCode::DrawInProgress);
+        
+        // Verify it's this player's turn; their seat's state is the one the round checks read
+        let seat = table.current_player_index as usize;
+        require!(table.players[seat] == ctx.accounts.player.key(), ErrorCode::NotPlayerTurn);
+        let player_state = &mut seats[seat];
+        require!(!player_state.is_folded, ErrorCode::PlayerFolded);
+        require!(player_state.is_active, ErrorCode::PlayerNotActive);
+        
+        // Time taken past the action window comes out of the player's time bank
+        let now = Clock::get()?.unix_timestamp;
+        charge_time_bank(table, player_state, now);
+        
+        // Calculate call amount
+        let call_amount = table.highest_bet.checked_sub(player_state.current_bet).unwrap();
+        
+        // Handle all-in if player doesn't have enough chips
+        let actual_call = std::cmp::min(call_amount, player_state.chips);
+        
+        // Update player state
+        player_state.chips = player_state.chips.checked_sub(actual_call).unwrap();
+        player_state.current_bet = player_state.current_bet.checked_add(actual_call).unwrap();
+        player_state.total_contributed = player_state.total_contributed.checked_add(actual_call).unwrap();
+        
+        // If the call used up the player's stack, they're all-in
+        if player_state.chips == 0 {
+            player_state.is_all_in = true;
         }
+        player_state.has_acted = true;
+        player_state.last_action_bet = table.highest_bet;
+        
+        // Update table state
+        table.pot = table.pot.checked_add(actual_call).unwrap();
+        
+        // Move to next player
+        advance_to_next_player(table, &seats)?;
+        table.turn_started_at = now;
+        
+        // Check if round is complete
+        check_round_completion(table, &mut seats)?;
+        
+        seats.save()
     }
-    
-    count
-}
 
-/// Generate a shuffled deck of cards (simplified for this example)
-fn generate_shuffled_deck(seed: u64) -> Vec<u8> {
-    let mut deck: Vec<u8> = (0..52).collect();
-    
-    // Simple Fisher-Yates shuffle based on seed
-    let mut rng = seed;
-    for i in (1..52).rev() {
-        // Simple LCG random number generator
-        rng = (rng.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407)) % u64::MAX;
-        let j = (rng % (i as u64 + 1)) as usize;
-        deck.swap(i, j);
-    }
+    /// Player folds their hand
+    pub fn fold(ctx: Context<PlayerAction>) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
+        
+        // Validate table state
+        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        require!(street_revealed(table, &seats), ErrorCode::StreetNotRevealed);
+        require!(table.round != Round::Draw, ErrorCode::DrawInProgress);
+        
+        // Verify it's this player's turn; their seat's state is the one the round checks read
+        let seat = table.current_player_index as usize;
+        require!(table.players[seat] == ctx.accounts.player.key(), ErrorCode::NotPlayerTurn);
+        let player_state = &mut seats[seat];
+        require!(!player_state.is_folded, ErrorCode::PlayerFolded);
+        require!(player_state.is_active, ErrorCode::PlayerNotActive);
+        
+        // Time taken past the action window comes out of the player's time bank
+        let now = Clock::get()?.unix_timestamp;
+        charge_time_bank(table, player_state, now);
+        
+        // Update player state
+        player_state.is_folded = true;
+        
+        // Move to next player
+        advance_to_next_player(table, &seats)?;
+        table.turn_started_at = now;
+        
+        // Check if only one player remains
+        let active_players = count_active_players(table, &seats);
+        if active_players == 1 {
+            award_uncontested_pot(table, &mut seats)?;
+            return seats.save();
+        }
+        
+        // Check if round is complete
+        check_round_completion(table, &mut seats)?;
+        
+        seats.save()
+    }
+
+    /// Player discards cards and draws replacements from the undealt deck (five-card draw)
+    ///
+    /// Bit `i` of `discard_mask` discards hole card `i`; a mask of 0 stands pat. The new cards
+    /// come from the next undealt positions of the committed deck, and the dealer then
+    /// re-delivers the player's encrypted hand with `deliver_hole_cards`.
+    pub fn draw_cards(ctx: Context<PlayerAction>, discard_mask: u8) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
+        
+        // Validate table state
+        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        require!(table.round == Round::Draw, ErrorCode::NotDrawRound);
+        
+        // Verify it's this player's turn; their seat's state is the one the draw checks read
+        let seat = table.current_player_index as usize;
+        require!(table.players[seat] == ctx.accounts.player.key(), ErrorCode::NotPlayerTurn);
+        let player_state = &mut seats[seat];
+        require!(!player_state.is_folded, ErrorCode::PlayerFolded);
+        require!(player_state.is_active, ErrorCode::PlayerNotActive);
+        require!(!player_state.has_acted, ErrorCode::AlreadyDrawn);
+        
+        // Time taken past the action window comes out of the player's time bank
+        let now = Clock::get()?.unix_timestamp;
+        charge_time_bank(table, player_state, now);
+        
+        // Only the player's own hole cards can be discarded
+        let hole_cards = hole_card_count(&table.game_variant);
+        require!(discard_mask >> hole_cards == 0, ErrorCode::InvalidDiscard);
+        require!(discard_mask.count_ones() <= MAX_DRAW_CARDS, ErrorCode::InvalidDiscard);
+        
+        draw_replacements(table, player_state, discard_mask);
+        player_state.has_acted = true;
+        
+        finish_draw(table, &mut seats)?;
+        table.turn_started_at = now;
+        
+        seats.save()
+    }
+
+    /// Determine winner and distribute pot at showdown
+    ///
+    /// At tournament tables anyone may settle the hand once the host has let
+    /// `HOST_GRACE_PERIOD` pass after the showdown reveal window.
+    pub fn showdown(ctx: Context<Showdown>) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
+        
+        // Validate table state
+        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        require!(table.round == Round::Showdown, ErrorCode::NotShowdownRound);
+        let clock = Clock::get()?;
+        require_host_or_expired(table, &ctx.accounts.caller.key(), table.showdown_deadline, clock.unix_timestamp)?;
+        
+        // Each reveal was only checked against its own cards and the board, so a card shown
+        // in two hands, or as someone's upcard, also proves a misdeal
+        let mut shown_cards = table.community_cards[..table.community_revealed as usize].to_vec();
+        for (i, player_pubkey) in table.players.iter().enumerate() {
+            if *player_pubkey == Pubkey::default() {
+                continue;
+            }
+            
+            // Revealed stud hands already include the player's upcards
+            let player_state = &seats[i];
+            if player_state.cards_revealed {
+                shown_cards.extend_from_slice(&player_state.cards[..hole_card_count(&table.game_variant)]);
+            } else {
+                shown_cards.extend_from_slice(&player_state.upcards[..player_state.upcards_revealed as usize]);
+            }
+        }
+        if has_repeated_card(&shown_cards) {
+            table.misdeal = true;
+        }
+        
+        // A proven misdeal returns every player's contribution, and the dealer who committed
+        // the invalid deck loses its approval so it cannot deal again
+        if table.misdeal {
+            let game_authority = &mut ctx.accounts.game_authority;
+            game_authority.dealers.retain(|dealer| *dealer != table.dealer);
+            emit!(DealerRemoved {
+                dealer: table.dealer,
+                table: table.key(),
+            });
+            
+            refund_contributions(table, &mut seats);
+            table.status = TableStatus::Finished;
+            table.finished_at = clock.unix_timestamp;
+            return seats.save();
+        }
+        
+        let board_cards = board_card_count(&table.game_variant);
+        require!(table.community_revealed as usize == board_cards, ErrorCode::BoardNotRevealed);
+        
+        // Wait for every live player to open their cards unless the reveal window has passed
+        let mut live_players = 0;
+        let mut revealed_players = 0;
+        for (i, player_pubkey) in table.players.iter().enumerate() {
+            if *player_pubkey == Pubkey::default() {
+                continue;
+            }
+            
+            let player_state = &seats[i];
+            if !player_state.is_folded && player_state.is_active {
+                live_players += 1;
+                if player_state.cards_revealed {
+                    revealed_players += 1;
+                }
+            }
+        }
+        require!(
+            revealed_players == live_players || clock.unix_timestamp > table.showdown_deadline,
+            ErrorCode::ShowdownRevealPending
+        );
+        
+        // Gather every seat's contribution and the hand strength of those still in the hand
+        // Players who never opened their cards muck, unless nobody did and the pots are split
+        let mut contributions = Vec::new();
+        let mut hand_values = vec![None; table.players.len()];
+        let mut low_values = vec![None; table.players.len()];
+        
+        for (i, player_pubkey) in table.players.iter().enumerate() {
+            if *player_pubkey == Pubkey::default() {
+                continue;
+            }
+            
+            let player_state = &seats[i];
+            let live = !player_state.is_folded && player_state.is_active;
+            contributions.push((i, player_state.total_contributed, live));
+            if !live {
+                continue;
+            }
+            if !player_state.cards_revealed {
+                if revealed_players == 0 {
+                    hand_values[i] = Some(0);
+                }
+                continue;
+            }
+            
+            // Evaluate hand strength from the hole cards and the board under the table's rules
+            let hole_cards = &player_state.cards[..hole_card_count(&table.game_variant)];
+            let board = &table.community_cards[..board_cards];
+            hand_values[i] = Some(evaluate_variant_hand(&table.game_variant, hole_cards, board));
+            if table.game_variant == GameVariant::OmahaHiLo {
+                low_values[i] = evaluate_low_hand(hole_cards, board);
+            }
+        }
+        
+        // Chips nobody matched go straight back to the player who bet them
+        if let Some((seat, excess)) = uncalled_excess(&contributions) {
+            let entry = contributions.iter_mut().find(|entry| entry.0 == seat).unwrap();
+            entry.1 = entry.1.checked_sub(excess).unwrap();
+            let player_state = &mut seats[seat];
+            player_state.total_contributed = player_state.total_contributed.checked_sub(excess).unwrap();
+            player_state.chips = player_state.chips.checked_add(excess).unwrap();
+        }
+        
+        // Award the main pot and every side pot, less the rake, to the best hands eligible for it
+        // A pot that only players who mucked unopened reached has no hand to claim it, so
+        // those players split it between themselves
+        for pot in build_pots(&contributions).iter() {
+            let amount = pot.amount.checked_sub(take_rake(table, pot.amount)).unwrap();
+            let best_hand_value = pot.eligible_seats.iter().filter_map(|&seat| hand_values[seat]).max();
+            let high_winners: Vec<usize> = pot
+                .eligible_seats
+                .iter()
+                .copied()
+                .filter(|&seat| hand_values[seat] == best_hand_value)
+                .collect();
+            
+            // In hi-lo the best qualifying low takes half, lower values being better
+            let best_low_value = pot.eligible_seats.iter().filter_map(|&seat| low_values[seat]).min();
+            let low_winners: Vec<usize> = match best_low_value {
+                Some(_) => pot
+                    .eligible_seats
+                    .iter()
+                    .copied()
+                    .filter(|&seat| low_values[seat] == best_low_value)
+                    .collect(),
+                None => Vec::new(),
+            };
+            
+            let shares = split_hi_lo(amount, &high_winners, &low_winners, table.dealer_index, table.max_players);
+            for (seat, share) in shares {
+                let winner_state = &mut seats[seat];
+                winner_state.chips = winner_state.chips.checked_add(share).unwrap();
+            }
+        }
+        
+        // End the game
+        table.status = TableStatus::Finished;
+        table.finished_at = clock.unix_timestamp;
+        
+        seats.save()
+    }
+
+    /// Void a hand the dealer has stopped revealing cards for
+    ///
+    /// Anyone may call this once `DEALER_REVEAL_WINDOW` has passed since the hand reached a
+    /// street, or showdown, whose cards the dealer still has not revealed. Every player gets
+    /// back what they put into the hand, which then finishes so the table can be reset or left.
+    pub fn void_hand<'info>(ctx: Context<'_, '_, '_, 'info, VoidHand<'info>>) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
+        
+        // Validate table state
+        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        require!(dealer_reveal_pending(table, &seats), ErrorCode::NoRevealPending);
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp > table.dealer_deadline, ErrorCode::DealerRevealWindowOpen);
+        
+        refund_contributions(table, &mut seats);
+        table.status = TableStatus::Finished;
+        table.finished_at = clock.unix_timestamp;
+        
+        seats.save()
+    }
+
+    /// Reset the table for a new game
+    ///
+    /// At tournament tables anyone may reset once the host has let `HOST_GRACE_PERIOD` pass
+    /// after the hand finished.
+    pub fn reset_table(ctx: Context<ResetTable>) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
+        
+        // Validate table state
+        require!(table.status == TableStatus::Finished, ErrorCode::GameNotFinished);
+        let now = Clock::get()?.unix_timestamp;
+        require_host_or_expired(table, &ctx.accounts.caller.key(), table.finished_at, now)?;
+        if table.game_mode == GameMode::MultiTable {
+            // Busted players must be eliminated with `eliminate_player` while their stacks
+            // from this hand are still known, unless they can still rebuy
+            let busted_seated = table.players.iter().enumerate().any(|(i, player_pubkey)| {
+                let player_state = &seats[i];
+                *player_pubkey != Pubkey::default() && player_state.chips == 0 && !can_rebuy(table, player_state)
+            });
+            require!(!busted_seated, ErrorCode::EliminationPending);
+        }
+        
+        // Reset table state
+        table.status = TableStatus::Waiting;
+        table.pot = 0;
+        table.round = Round::NotStarted;
+        table.highest_bet = 0;
+        table.commit_count = 0;
+        table.commit_deadline = 0;
+        table.reveal_count = 0;
+        table.reveal_deadline = 0;
+        table.shuffle_entropy = [0; 32];
+        table.deck_commitment = [0; 32];
+        table.community_revealed = 0;
+        table.showdown_deadline = 0;
+        table.dealer_deadline = 0;
+        table.misdeal = false;
+        table.rake = 0;
+        
+        // Busted players are placed by the stack they started the hand with, so eliminate them
+        // before their contributions are cleared
+        let is_tournament = table.game_mode != GameMode::CashGame;
+        if table.game_mode == GameMode::SitAndGo {
+            eliminate_busted_players(table, &mut seats);
+        }
+        
+        // Reset player states; busted players waiting to rebuy stay sitting out
+        for player_state in seats.iter_mut() {
+            if player_state.is_active {
+                player_state.is_folded = false;
+                player_state.current_bet = 0;
+                player_state.total_contributed = 0;
+                player_state.is_all_in = false;
+                player_state.has_acted = false;
+                player_state.last_action_bet = 0;
+                player_state.shuffle_commitment = [0; 32];
+                player_state.has_revealed = false;
+                player_state.is_sitting_out = player_state.is_away || (is_tournament && player_state.chips == 0);
+                player_state.encrypted_cards = Vec::new();
+                player_state.cards_revealed = false;
+                player_state.upcards_revealed = 0;
+            }
+        }
+        
+        seats.save()
+    }
+
+    /// Pay out the rake a table has taken since it was last collected
+    ///
+    /// Anyone may call this. The host's share, set aside hand by hand while they hosted, goes
+    /// straight to them, and the protocol and referral shares are held in the treasury until
+    /// withdrawn.
+    pub fn collect_rake(ctx: Context<CollectRake>) -> Result<()> {
+        let table = &mut ctx.accounts.table;
+        let rake = table.rake_due;
+        let host_share = table.host_rake_due;
+        table.rake_due = 0;
+        table.host_rake_due = 0;
+        let table_id = table.table_id.clone();
+        let bump = table.bump;
+        
+        if rake > 0 || host_share > 0 {
+            // What isn't the host's is split between the protocol and referral pool in their ratio
+            let game_authority = &mut ctx.accounts.game_authority;
+            let pool_bps = game_authority.protocol_fee_bps as u64 + game_authority.referral_fee_bps as u64;
+            let referral_share = match pool_bps {
+                0 => 0,
+                _ => rake.checked_mul(game_authority.referral_fee_bps as u64).unwrap() / pool_bps,
+            };
+            let protocol_share = rake.checked_sub(referral_share).unwrap();
+            game_authority.total_fees_collected = game_authority
+                .total_fees_collected
+                .checked_add(rake.checked_add(host_share).unwrap())
+                .unwrap();
+            game_authority.protocol_fees = game_authority.protocol_fees.checked_add(protocol_share).unwrap();
+            game_authority.referral_fees = game_authority.referral_fees.checked_add(referral_share).unwrap();
+            
+            let seeds = &[
+                b"table".as_ref(),
+                table_id.as_bytes(),
+                &[bump],
+            ];
+            let signer = &[&seeds[..]];
+            
+            let payments = [
+                (ctx.accounts.host_token_account.to_account_info(), host_share),
+                (ctx.accounts.treasury.to_account_info(), rake),
+            ];
+            for (to, amount) in payments {
+                if amount == 0 {
+                    continue;
+                }
+                
+                let cpi_accounts = Transfer {
+                    from: ctx.accounts.table_vault.to_account_info(),
+                    to,
+                    authority: ctx.accounts.table.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+                token::transfer(cpi_ctx, amount)?;
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// Pay a tournament player their share of the prize pool for their finishing place
+    pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
+        let table = &ctx.accounts.table;
+        let player_state = &mut ctx.accounts.player_state;
+        
+        // Validate table and player state
+        require!(table.status == TableStatus::Completed, ErrorCode::TournamentNotComplete);
+        require!(!player_state.prize_claimed, ErrorCode::PrizeAlreadyClaimed);
+        
+        let prize = prize_for_place(table.prize_pool, &table.payouts_bps, tournament_place(player_state.finishing_place));
+        require!(prize > 0, ErrorCode::NoPrize);
+        player_state.prize_claimed = true;
+        
+        // Transfer the prize from table vault to player
+        let seeds = &[
+            b"table".as_ref(),
+            table.table_id.as_bytes(),
+            &[table.bump],
+        ];
+        let signer = &[&seeds[..]];
+        
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.table_vault.to_account_info(),
+            to: ctx.accounts.player_token_account.to_account_info(),
+            authority: ctx.accounts.table.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, prize)?;
+        
+        Ok(())
+    }
+
+    /// Add chips to a cash game stack between hands, up to the table's maximum stack
+    pub fn top_up(ctx: Context<Rebuy>, amount: u64) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_JOIN)?;
+        let table = &ctx.accounts.table;
+        let player_state = &ctx.accounts.player_state;
+        
+        // Validate table and player state
+        require!(table.game_mode == GameMode::CashGame, ErrorCode::RebuyNotAllowed);
+        require!(table.status != TableStatus::Playing, ErrorCode::GameInProgress);
+        require!(player_state.is_active, ErrorCode::PlayerNotActive);
+        require!(amount > 0, ErrorCode::InvalidTopUp);
+        let max_stack = if table.max_stack == 0 { table.max_buy_in } else { table.max_stack };
+        require!(
+            player_state.chips.checked_add(amount).unwrap() <= max_stack,
+            ErrorCode::StackLimitExceeded
+        );
+        
+        deposit_chips(&ctx, amount)?;
+        let player_state = &mut ctx.accounts.player_state;
+        player_state.chips = player_state.chips.checked_add(amount).unwrap();
+        
+        Ok(())
+    }
+
+    /// Buy another starting stack in a sit-and-go during its rebuy period
+    ///
+    /// Only players at or below the starting stack may rebuy, and the buy-in joins the prize
+    /// pool. A busted player can rebuy to sit back in before being eliminated.
+    pub fn rebuy(ctx: Context<Rebuy>) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_JOIN)?;
+        let table = &ctx.accounts.table;
+        
+        // Validate table and player state
+        require!(table.game_mode == GameMode::SitAndGo, ErrorCode::RebuyNotAllowed);
+        require_rebuy_open(table, &ctx.accounts.player_state)?;
+        
+        let buy_in = table.buy_in;
+        deposit_chips(&ctx, buy_in)?;
+        
+        credit_rebuy(&ctx.accounts.table, &mut ctx.accounts.player_state);
+        let table = &mut ctx.accounts.table;
+        table.prize_pool = table.prize_pool.checked_add(buy_in).unwrap();
+        
+        Ok(())
+    }
+
+    /// Take the one-time sit-and-go add-on at the break after the rebuy period
+    pub fn add_on(ctx: Context<Rebuy>) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_JOIN)?;
+        let table = &ctx.accounts.table;
+        
+        // Validate table and player state
+        require!(table.game_mode == GameMode::SitAndGo, ErrorCode::RebuyNotAllowed);
+        require_add_on_open(table, &ctx.accounts.player_state)?;
+        
+        let buy_in = table.buy_in;
+        deposit_chips(&ctx, buy_in)?;
+        
+        let add_on_chips = ctx.accounts.table.add_on_chips;
+        let player_state = &mut ctx.accounts.player_state;
+        player_state.chips = player_state.chips.checked_add(add_on_chips).unwrap();
+        player_state.add_on_taken = true;
+        
+        let table = &mut ctx.accounts.table;
+        table.prize_pool = table.prize_pool.checked_add(buy_in).unwrap();
+        
+        Ok(())
+    }
+
+    /// Buy another starting stack in a multi-table tournament during its rebuy period
+    ///
+    /// Works like a sit-and-go rebuy, except the buy-in goes to the tournament's prize pool.
+    pub fn tournament_rebuy(ctx: Context<TournamentRebuy>) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_JOIN)?;
+        
+        // Validate tournament, table and player state
+        require!(ctx.accounts.tournament.status == TournamentStatus::Running, ErrorCode::TournamentNotStarted);
+        require_rebuy_open(&ctx.accounts.table, &ctx.accounts.player_state)?;
+        
+        let buy_in = ctx.accounts.tournament.buy_in;
+        deposit_tournament_chips(&ctx, buy_in)?;
+        
+        credit_rebuy(&ctx.accounts.table, &mut ctx.accounts.player_state);
+        let tournament = &mut ctx.accounts.tournament;
+        tournament.prize_pool = tournament.prize_pool.checked_add(buy_in).unwrap();
+        
+        Ok(())
+    }
+
+    /// Take the one-time multi-table tournament add-on at the break after the rebuy period
+    pub fn tournament_add_on(ctx: Context<TournamentRebuy>) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_JOIN)?;
+        
+        // Validate tournament, table and player state
+        require!(ctx.accounts.tournament.status == TournamentStatus::Running, ErrorCode::TournamentNotStarted);
+        require_add_on_open(&ctx.accounts.table, &ctx.accounts.player_state)?;
+        
+        let buy_in = ctx.accounts.tournament.buy_in;
+        deposit_tournament_chips(&ctx, buy_in)?;
+        
+        let add_on_chips = ctx.accounts.table.add_on_chips;
+        let player_state = &mut ctx.accounts.player_state;
+        player_state.chips = player_state.chips.checked_add(add_on_chips).unwrap();
+        player_state.add_on_taken = true;
+        
+        let tournament = &mut ctx.accounts.tournament;
+        tournament.prize_pool = tournament.prize_pool.checked_add(buy_in).unwrap();
+        
+        Ok(())
+    }
+
+    /// Create a multi-table tournament with a shared prize pool and blind schedule
+    ///
+    /// Tables are added with `add_tournament_table` and filled by `register_tournament`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_tournament(
+        ctx: Context<CreateTournament>,
+        tournament_id: String,
+        buy_in: u64,
+        max_entrants: u16,
+        seats_per_table: u8,
+        dealer: Pubkey,
+        betting_structure: BettingStructure,
+        game_variant: GameVariant,
+        config: TournamentConfig,
+        rebuys: RebuyConfig,
+    ) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_CREATE)?;
+        require!(tournament_id.len() <= 32, ErrorCode::TableIdTooLong);
+        require!(ctx.accounts.game_authority.dealers.contains(&dealer), ErrorCode::UnapprovedDealer);
+        require!(dealer != ctx.accounts.host.key(), ErrorCode::DealerCannotPlay);
+        require!(
+            seats_per_table >= 2 && seats_per_table <= max_seats(&game_variant),
+            ErrorCode::InvalidPlayerCount
+        );
+        require!(
+            max_entrants >= 2 && max_entrants as usize <= MAX_TOURNAMENT_TABLES * seats_per_table as usize,
+            ErrorCode::InvalidPlayerCount
+        );
+        let max_places = std::cmp::min(max_entrants as usize, MAX_TOURNAMENT_PAYOUTS);
+        validate_tournament_config(&config, max_places, &game_variant)?;
+        
+        // Tables deal hands independently, so only a shared clock keeps their levels in step
+        require!(config.level_clock == LevelClock::Time, ErrorCode::InvalidBlindSchedule);
+        
+        let tournament = &mut ctx.accounts.tournament;
+        tournament.host = ctx.accounts.host.key();
+        tournament.tournament_id = tournament_id;
+        tournament.buy_in = buy_in;
+        tournament.max_entrants = max_entrants;
+        tournament.seats_per_table = seats_per_table;
+        tournament.dealer = dealer;
+        tournament.betting_structure = betting_structure;
+        tournament.game_variant = game_variant;
+        tournament.starting_stack = config.starting_stack;
+        tournament.blind_levels = config.blind_levels;
+        tournament.level_duration = config.level_duration;
+        tournament.payouts_bps = config.payouts_bps;
+        tournament.max_rebuys = rebuys.max_rebuys;
+        tournament.rebuy_levels = rebuys.rebuy_levels;
+        tournament.add_on_chips = rebuys.add_on_chips;
+        tournament.status = TournamentStatus::Registering;
+        tournament.entrant_count = 0;
+        tournament.players_remaining = 0;
+        tournament.prize_pool = 0;
+        tournament.started_at = 0;
+        tournament.tables = Vec::new();
+        tournament.table_counts = Vec::new();
+        tournament.final_table = Pubkey::default();
+        tournament.vault = ctx.accounts.tournament_vault.key();
+        tournament.bump = *ctx.bumps.get("tournament").unwrap();
+        
+        Ok(())
+    }
+
+    /// Add a table to a tournament that is still registering players
+    pub fn add_tournament_table(ctx: Context<AddTournamentTable>, table_id: String) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_CREATE)?;
+        let tournament = &mut ctx.accounts.tournament;
+        
+        // Validate tournament state
+        require!(table_id.len() <= 32, ErrorCode::TableIdTooLong);
+        require!(tournament.status == TournamentStatus::Registering, ErrorCode::RegistrationClosed);
+        require!(tournament.tables.len() < MAX_TOURNAMENT_TABLES, ErrorCode::TooManyTables);
+        
+        // The table plays the tournament's game at its first blind level and has no buy-in of
+        // its own; the host runs it like any other table
+        let first_level = tournament.blind_levels[0];
+        let table = &mut ctx.accounts.table;
+        table.host = tournament.host;
+        table.table_id = table_id;
+        table.buy_in = 0;
+        table.small_blind = first_level.small_blind;
+        table.big_blind = first_level.big_blind;
+        table.ante = first_level.ante;
+        table.max_players = tournament.seats_per_table;
+        table.is_private = false;
+        table.betting_structure = tournament.betting_structure.clone();
+        table.game_variant = tournament.game_variant.clone();
+        table.vrf_oracle = Pubkey::default();
+        table.dealer = tournament.dealer;
+        table.status = TableStatus::Waiting;
+        table.pot = 0;
+        table.current_player_index = 0;
+        table.dealer_index = 0;
+        table.round = Round::NotStarted;
+        table.players = vec![Pubkey::default(); tournament.seats_per_table as usize];
+        table.player_count = 0;
+        table.hands_played = 0;
+        table.game_mode = GameMode::MultiTable;
+        table.starting_stack = tournament.starting_stack;
+        table.blind_levels = tournament.blind_levels.clone();
+        table.level_clock = LevelClock::Time;
+        table.level_duration = tournament.level_duration;
+        table.level_started_at = 0;
+        table.tournament = tournament.key();
+        table.max_rebuys = tournament.max_rebuys;
+        table.rebuy_levels = tournament.rebuy_levels;
+        table.add_on_chips = tournament.add_on_chips;
+        table.action_timeout = DEFAULT_ACTION_TIMEOUT;
+        table.time_bank = DEFAULT_TIME_BANK;
+        table.bump = *ctx.bumps.get("table").unwrap();
+        escrow_crank_bounty(&ctx.accounts.host, table, &ctx.accounts.system_program)?;
+        
+        tournament.tables.push(table.key());
+        tournament.table_counts.push(0);
+        
+        Ok(())
+    }
+
+    /// Register for a tournament, paying the buy-in into its prize pool
+    ///
+    /// Entrants are seated at one of the emptiest tables so registration stays balanced.
+    pub fn register_tournament(ctx: Context<RegisterTournament>, encryption_key: [u8; 32]) -> Result<()> {
+        require_not_paused(&ctx.accounts.game_authority, PAUSE_JOIN)?;
+        let tournament = &mut ctx.accounts.tournament;
+        let table = &mut ctx.accounts.table;
+        
+        require!(encryption_key != [0; 32], ErrorCode::InvalidEncryptionKey);
+        require!(ctx.accounts.player.key() != tournament.dealer, ErrorCode::DealerCannotPlay);
+        
+        // Validate tournament and table state
+        require!(tournament.status == TournamentStatus::Registering, ErrorCode::RegistrationClosed);
+        require!(tournament.entrant_count < tournament.max_entrants, ErrorCode::TableFull);
+        let table_index = tournament_table_index(tournament, &table.key())?;
+        let fewest_players = tournament.table_counts.iter().copied().min().unwrap();
+        require!(tournament.table_counts[table_index] == fewest_players, ErrorCode::TablesUnbalanced);
+        
+        seat_player(table, ctx.accounts.player.key())?;
+        tournament.table_counts[table_index] = tournament.table_counts[table_index].checked_add(1).unwrap();
+        tournament.entrant_count = tournament.entrant_count.checked_add(1).unwrap();
+        tournament.players_remaining = tournament.players_remaining.checked_add(1).unwrap();
+        tournament.prize_pool = tournament.prize_pool.checked_add(tournament.buy_in).unwrap();
+        
+        // Transfer buy-in from player to the tournament vault
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.player_token_account.to_account_info(),
+            to: ctx.accounts.tournament_vault.to_account_info(),
+            authority: ctx.accounts.player.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        token::transfer(cpi_ctx, tournament.buy_in)?;
+        
+        // Create player state
+        let player_state = &mut ctx.accounts.player_state;
+        player_state.player = ctx.accounts.player.key();
+        player_state.table = table.key();
+        player_state.encryption_key = encryption_key;
+        player_state.chips = tournament.starting_stack;
+        player_state.is_active = true;
+        player_state.is_folded = false;
+        player_state.current_bet = 0;
+        player_state.total_contributed = 0;
+        player_state.cards = [0; MAX_HOLE_CARDS]; // Will be set when revealed at showdown
+        player_state.time_bank = table.time_bank;
+        player_state.bump = *ctx.bumps.get("player_state").unwrap();
+        
+        // Create the tournament entry that follows the player between tables
+        let entry = &mut ctx.accounts.entry;
+        entry.tournament = tournament.key();
+        entry.player = ctx.accounts.player.key();
+        entry.table = table.key();
+        entry.finishing_place = 0;
+        entry.prize_claimed = false;
+        entry.bump = *ctx.bumps.get("entry").unwrap();
+        
+        Ok(())
+    }
+
+    /// Close registration and start the tournament clock
+    ///
+    /// Each table picks up the start time with `sync_tournament_table` before its first hand.
+    pub fn start_tournament(ctx: Context<StartTournament>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        
+        // Validate tournament state
+        require!(tournament.status == TournamentStatus::Registering, ErrorCode::RegistrationClosed);
+        require!(tournament.entrant_count >= 2, ErrorCode::NotEnoughPlayers);
+        
+        // The payout table was checked against a full field; pay only as many places as entered
+        tournament.payouts_bps = fit_payouts(&tournament.payouts_bps, tournament.entrant_count as usize);
+        
+        let clock = Clock::get()?;
+        tournament.status = TournamentStatus::Running;
+        tournament.started_at = clock.unix_timestamp;
+        if tournament.tables.len() == 1 {
+            tournament.final_table = tournament.tables[0];
+        }
+        
+        Ok(())
+    }
+
+    /// Bring a tournament table's blinds up to the tournament clock between hands
+    ///
+    /// Anyone may call this. Every table shares the tournament's start time, so all of them
+    /// move through the blind schedule together.
+    pub fn sync_tournament_table(ctx: Context<SyncTournamentTable>) -> Result<()> {
+        let tournament = &ctx.accounts.tournament;
+        let table = &mut ctx.accounts.table;
+        
+        // Validate tournament and table state
+        require!(tournament.status == TournamentStatus::Running, ErrorCode::TournamentNotStarted);
+        require!(table.tournament == tournament.key(), ErrorCode::TableNotInTournament);
+        require!(table.status != TableStatus::Playing, ErrorCode::GameInProgress);
+        
+        if table.level_started_at == 0 {
+            table.blind_level = 0;
+            table.level_started_at = tournament.started_at;
+        }
+        update_blind_level(table)?;
+        
+        Ok(())
+    }
+
+    /// Move a player to another table of the tournament between hands
+    ///
+    /// Anyone may call this to even out the tables: a player can move from one of the fullest
+    /// tables to a table with at least two fewer players, or off a table that has been broken.
+    /// The caller pays rent for the new player state and gets the old one's back.
+    pub fn move_player(ctx: Context<MovePlayer>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        let from_table = &mut ctx.accounts.from_table;
+        let to_table = &mut ctx.accounts.to_table;
+        let from_player_state = &ctx.accounts.from_player_state;
+        
+        // Validate tournament and table state; both tables must be between hands with no
+        // shuffle under way
+        require!(tournament.status == TournamentStatus::Running, ErrorCode::TournamentNotStarted);
+        require!(from_table.tournament == tournament.key(), ErrorCode::TableNotInTournament);
+        require!(from_table.key() != to_table.key(), ErrorCode::TablesUnbalanced);
+        require!(
+            from_table.status == TableStatus::Waiting || from_table.status == TableStatus::Closed,
+            ErrorCode::TableNotWaiting
+        );
+        require!(to_table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
+        for table in [&**from_table, &**to_table] {
+            require!(table.commit_count == 0 && table.reveal_deadline == 0, ErrorCode::ShuffleInProgress);
+            require!(table.pending_randomness == [0; 32], ErrorCode::RandomnessPending);
+        }
+        require!(from_player_state.chips > 0, ErrorCode::EliminationPending);
+        
+        // A broken table's players already left the tournament's table counts
+        let from_key = from_table.key();
+        let live_from_table = (from_table.status != TableStatus::Closed).then_some(&from_key);
+        move_table_count(tournament, live_from_table, &to_table.key())?;
+        
+        let player = ctx.accounts.player.key();
+        unseat_player(from_table, player)?;
+        seat_player(to_table, player)?;
+        
+        // Carry the stack and encryption key over to the player's state at the new table
+        let to_player_state = &mut ctx.accounts.to_player_state;
+        to_player_state.player = player;
+        to_player_state.table = to_table.key();
+        to_player_state.encryption_key = from_player_state.encryption_key;
+        to_player_state.chips = from_player_state.chips;
+        to_player_state.is_active = true;
+        to_player_state.is_folded = false;
+        to_player_state.current_bet = 0;
+        to_player_state.total_contributed = 0;
+        to_player_state.cards = [0; MAX_HOLE_CARDS];
+        to_player_state.time_bank = from_player_state.time_bank;
+        to_player_state.rebuys = from_player_state.rebuys;
+        to_player_state.add_on_taken = from_player_state.add_on_taken;
+        to_player_state.bump = *ctx.bumps.get("to_player_state").unwrap();
+        
+        ctx.accounts.entry.table = to_table.key();
+        
+        Ok(())
+    }
+
+    /// Break one of the emptiest tables once the remaining players fit at the others
+    ///
+    /// Anyone may call this between hands. Its players are then moved off with `move_player`,
+    /// and when a single table is left it becomes the final table. Busted players still
+    /// seated are eliminated from the closed table with `eliminate_player`.
+    pub fn break_table<'info>(ctx: Context<'_, '_, '_, 'info, BreakTable<'info>>) -> Result<()> {
+        let seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let tournament = &mut ctx.accounts.tournament;
+        let table = &mut ctx.accounts.table;
+        
+        // Validate tournament and table state
+        require!(tournament.status == TournamentStatus::Running, ErrorCode::TournamentNotStarted);
+        require!(table.status == TableStatus::Waiting, ErrorCode::TableNotWaiting);
+        require!(table.commit_count == 0 && table.reveal_deadline == 0, ErrorCode::ShuffleInProgress);
+        require!(table.pending_randomness == [0; 32], ErrorCode::RandomnessPending);
+        
+        // The blinds stop rising once the table closes, so busted players who can still rebuy
+        // must rebuy or see the rebuy period out here first
+        let rebuy_pending = table.players.iter().enumerate().any(|(i, player_pubkey)| {
+            *player_pubkey != Pubkey::default() && seats[i].chips == 0 && can_rebuy(table, &seats[i])
+        });
+        require!(!rebuy_pending, ErrorCode::RebuyAvailable);
+        
+        remove_tournament_table(tournament, &table.key())?;
+        table.status = TableStatus::Closed;
+        
+        Ok(())
+    }
+
+    /// Record a tournament player's elimination after they bust
+    ///
+    /// Anyone may call this once the hand is over and before the table is reset, or after the
+    /// table is broken. Players busting in the same hand are eliminated in order of the stacks
+    /// they started it with, smallest first, and the tournament completes when one player
+    /// remains.
+    pub fn eliminate_player(ctx: Context<EliminatePlayer>) -> Result<()> {
+        let seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let tournament = &mut ctx.accounts.tournament;
+        let table = &mut ctx.accounts.table;
+        let player_state = &mut ctx.accounts.player_state;
+        
+        // Validate tournament, table and player state; players who busted in an earlier hand
+        // and let their rebuy lapse can also go out between hands, or from a broken table
+        require!(tournament.status == TournamentStatus::Running, ErrorCode::TournamentNotStarted);
+        require!(table.tournament == tournament.key(), ErrorCode::TableNotInTournament);
+        let closed = table.status == TableStatus::Closed;
+        let between_hands = (table.status == TableStatus::Waiting && table.commit_count == 0) || closed;
+        require!(table.status == TableStatus::Finished || between_hands, ErrorCode::GameNotFinished);
+        require!(player_state.is_active && player_state.chips == 0, ErrorCode::PlayerNotBusted);
+        require!(!can_rebuy(table, player_state), ErrorCode::RebuyAvailable);
+        
+        // A player who started the hand with fewer chips must go out first
+        let busted_first = table.players.iter().enumerate().any(|(i, player_pubkey)| {
+            let other = &seats[i];
+            *player_pubkey != Pubkey::default()
+                && other.chips == 0
+                && !can_rebuy(table, other)
+                && other.total_contributed < player_state.total_contributed
+        });
+        require!(!busted_first, ErrorCode::EliminationPending);
+        
+        eliminate_busted_player(table, player_state, tournament.players_remaining)?;
+        ctx.accounts.entry.finishing_place = player_state.finishing_place;
+        tournament.players_remaining = tournament.players_remaining.checked_sub(1).unwrap();
+        
+        // A broken table's players already left the tournament's table counts
+        if !closed {
+            let table_index = tournament_table_index(tournament, &table.key())?;
+            tournament.table_counts[table_index] = tournament.table_counts[table_index].checked_sub(1).unwrap();
+        }
+        
+        // The last player standing wins and play stops
+        if tournament.players_remaining == 1 {
+            tournament.status = TournamentStatus::Completed;
+            if !closed {
+                table.status = TableStatus::Completed;
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// Pay a multi-table tournament player their share of the prize pool
+    pub fn claim_tournament_prize(ctx: Context<ClaimTournamentPrize>) -> Result<()> {
+        let tournament = &ctx.accounts.tournament;
+        let entry = &mut ctx.accounts.entry;
+        
+        // Validate tournament and entry state
+        require!(tournament.status == TournamentStatus::Completed, ErrorCode::TournamentNotComplete);
+        require!(!entry.prize_claimed, ErrorCode::PrizeAlreadyClaimed);
+        
+        let prize = prize_for_place(tournament.prize_pool, &tournament.payouts_bps, tournament_place(entry.finishing_place));
+        require!(prize > 0, ErrorCode::NoPrize);
+        entry.prize_claimed = true;
+        
+        // Transfer the prize from the tournament vault to player
+        let seeds = &[
+            b"tournament".as_ref(),
+            tournament.tournament_id.as_bytes(),
+            &[tournament.bump],
+        ];
+        let signer = &[&seeds[..]];
+        
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.tournament_vault.to_account_info(),
+            to: ctx.accounts.player_token_account.to_account_info(),
+            authority: ctx.accounts.tournament.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, prize)?;
+        
+        Ok(())
+    }
+
+    /// Act for a player who let their action clock run out
+    ///
+    /// Anyone may call this once the action window and the player's time bank have both run
+    /// out. The stalled player checks if they can, stands pat in the draw, and otherwise folds;
+    /// tables configured to do so also sit them out of later hands.
+    ///
+    /// Every crank is paid. When a cash player folds or stands pat, the caller gets
+    /// `TIMEOUT_CRANK_REWARD_BPS` of a betting unit out of their stack. A plain check costs the
+    /// player nothing and tournament chips are not backed by the vault, so those cranks, and
+    /// any the stack cannot cover, are paid `TIMEOUT_CRANK_BOUNTY` lamports from the table's
+    /// crank bounty instead.
+    pub fn timeout_action<'info>(
+        ctx: Context<'_, '_, '_, 'info, TimeoutAction<'info>>,
+    ) -> Result<()> {
+        let mut seats = Seats::load(&ctx.accounts.table, ctx.remaining_accounts)?;
+        let table = &mut ctx.accounts.table;
+        
+        // Validate table state
+        require!(table.status == TableStatus::Playing, ErrorCode::GameNotInProgress);
+        require!(table.round != Round::Showdown, ErrorCode::NoActionPending);
+        require!(street_revealed(table, &seats), ErrorCode::StreetNotRevealed);
+        
+        // Only the player whose turn it is can be timed out
+        let seat = table.current_player_index as usize;
+        let player_state = &mut seats[seat];
+        
+        // The player's time bank extends the deadline, and the timeout uses it up
+        let clock = Clock::get()?;
+        let deadline = table
+            .turn_started_at
+            .checked_add(table.action_timeout)
+            .unwrap()
+            .checked_add(player_state.time_bank)
+            .unwrap();
+        require!(clock.unix_timestamp > deadline, ErrorCode::ActionTimeNotExpired);
+        charge_time_bank(table, player_state, clock.unix_timestamp);
+        
+        player_state.has_acted = true;
+        if table.sit_out_on_timeout {
+            player_state.is_away = true;
+        }
+        
+        let checked = table.round != Round::Draw
+            && (table.highest_bet == 0 || player_state.current_bet == table.highest_bet);
+        if checked {
+            player_state.last_action_bet = table.highest_bet;
+        } else if table.round != Round::Draw {
+            player_state.is_folded = true;
+        }
+        
+        // The reward comes out of the stack before the action moves on, so a player it leaves
+        // without chips is already all-in when the round is checked for completion
+        let reward = if checked { 0 } else { take_timeout_reward(table, player_state) };
+        let bounty = if reward == 0 { take_crank_bounty(table) } else { 0 };
+        
+        if table.round == Round::Draw {
+            finish_draw(table, &mut seats)?;
+        } else {
+            advance_to_next_player(table, &seats)?;
+            if !checked && count_active_players(table, &seats) == 1 {
+                award_uncontested_pot(table, &mut seats)?;
+            } else {
+                check_round_completion(table, &mut seats)?;
+            }
+        }
+        table.turn_started_at = clock.unix_timestamp;
+        
+        let table_id = table.table_id.clone();
+        let bump = table.bump;
+        if reward > 0 {
+            let seeds = &[
+                b"table".as_ref(),
+                table_id.as_bytes(),
+                &[bump],
+            ];
+            let signer = &[&seeds[..]];
+            
+            let table_vault = ctx.accounts.table_vault.as_ref().ok_or(ErrorCode::InvalidVault)?;
+            let cranker_token_account = ctx.accounts.cranker_token_account.as_ref().ok_or(ErrorCode::InvalidVault)?;
+            let cpi_accounts = Transfer {
+                from: table_vault.to_account_info(),
+                to: cranker_token_account.to_account_info(),
+                authority: ctx.accounts.table.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, reward)?;
+        }
+        
+        // The bounty is held in the table account itself, above its rent-exempt minimum
+        if bounty > 0 {
+            let table_info = ctx.accounts.table.to_account_info();
+            let cranker_info = ctx.accounts.cranker.to_account_info();
+            **table_info.try_borrow_mut_lamports()? = table_info.lamports().checked_sub(bounty).unwrap();
+            **cranker_info.try_borrow_mut_lamports()? = cranker_info.lamports().checked_add(bounty).unwrap();
+        }
+        
+        seats.save()
+    }
+
+    /// Add lamports to a table's crank bounty, which pays for timeouts no stack pays for
+    ///
+    /// Anyone may top the bounty up, typically the host of a long-running table.
+    pub fn fund_crank_bounty(ctx: Context<FundCrankBounty>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidCrankBounty);
+        
+        let cpi_accounts = anchor_lang::system_program::Transfer {
+            from: ctx.accounts.funder.to_account_info(),
+            to: ctx.accounts.table.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        anchor_lang::system_program::transfer(cpi_ctx, amount)?;
+        
+        let table = &mut ctx.accounts.table;
+        table.crank_bounty = table.crank_bounty.checked_add(amount).unwrap();
+        
+        Ok(())
+    }
+
+    /// Sit out of future hands, or return to play
+    ///
+    /// Before the shuffle starts the change applies to the next hand; otherwise it takes effect
+    /// when the table is reset.
+    pub fn set_sitting_out(ctx: Context<SitOut>, sitting_out: bool) -> Result<()> {
+        let table = &ctx.accounts.table;
+        let player_state = &mut ctx.accounts.player_state;
+        
+        // Validate player state
+        require!(player_state.is_active, ErrorCode::PlayerNotActive);
+        require!(sitting_out || player_state.chips > 0, ErrorCode::InsufficientChips);
+        
+        player_state.is_away = sitting_out;
+        if table.status == TableStatus::Waiting && table.commit_count == 0 {
+            player_state.is_sitting_out = sitting_out;
+        }
+        
+        Ok(())
+    }
+
+    /// Leave a table and withdraw chips
+    pub fn leave_table(ctx: Context<LeaveTable>) -> Result<()> {
+        let table = &mut ctx.accounts.table;
+        let player_state = &mut ctx.accounts.player_state;
+        
+        // Validate table state
+        require!(
+            table.status == TableStatus::Waiting || table.status == TableStatus::Finished,
+            ErrorCode::CannotLeaveActiveGame
+        );
+        
+        // Find player's index in the table
+        let mut player_index = table.max_players as usize;
+        for (i, player_pubkey) in table.players.iter().enumerate() {
+            if *player_pubkey == ctx.accounts.player.key() {
+                player_index = i;
+                break;
+            }
+        }
+        require!(player_index < table.max_players as usize, ErrorCode::PlayerNotAtTable);
+        
+        // Tournament chips cannot be cashed out; an entrant may only withdraw their buy-in
+        // before the first hand is dealt
+        let withdrawal = match table.game_mode {
+            GameMode::SitAndGo => {
+                require!(table.hands_played == 0, ErrorCode::TournamentInProgress);
+                table.prize_pool = table.prize_pool.checked_sub(table.buy_in).unwrap();
+                table.buy_in
+            }
+            GameMode::MultiTable => return Err(ErrorCode::TournamentInProgress.into()),
+            GameMode::CashGame => {
+                let clock = Clock::get()?;
+                record_departure(table, ctx.accounts.player.key(), player_state.chips, clock.unix_timestamp);
+                
+                // A departing host takes the rake share they earned with them
+                let mut withdrawal = player_state.chips;
+                if ctx.accounts.player.key() == table.host {
+                    withdrawal = withdrawal.checked_add(table.host_rake_due).unwrap();
+                    table.host_rake_due = 0;
+                }
+                withdrawal
+            }
+        };
+        
+        // Remove player from table
+        table.players[player_index] = Pubkey::default();
+        table.player_count = table.player_count.checked_sub(1).unwrap();
+        
+        // Withdraw from the pending shuffle so the remaining players can still complete it
+        if player_state.shuffle_commitment != [0; 32] {
+            table.commit_count = table.commit_count.checked_sub(1).unwrap();
+            if player_state.has_revealed {
+                table.reveal_count = table.reveal_count.checked_sub(1).unwrap();
+            }
+            player_state.shuffle_commitment = [0; 32];
+            player_state.has_revealed = false;
+        }
+        
+        // Mark player as inactive
+        player_state.is_active = false;
+        player_state.chips = 0;
+        
+        // If host is leaving and other players remain, transfer host status
+        if ctx.accounts.player.key() == table.host && table.player_count > 0 {
+            // Find first active player to be new host
+            for player_pubkey in table.players.iter() {
+                if *player_pubkey != Pubkey::default() && *player_pubkey != ctx.accounts.player.key() {
+                    table.host = *player_pubkey;
+                    break;
+                }
+            }
+        }
+        
+        // If no players left, close the table
+        if table.player_count == 0 {
+            // In a real implementation, we would close the table account here
+            // and return the rent to the host
+        }
+        
+        // Transfer chips from table vault to player
+        let table_id = table.table_id.clone();
+        let bump = table.bump;
+        let seeds = &[
+            b"table".as_ref(),
+            table_id.as_bytes(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+        
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.table_vault.to_account_info(),
+            to: ctx.accounts.player_token_account.to_account_info(),
+            authority: ctx.accounts.table.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, withdrawal)?;
+        
+        Ok(())
+    }
+}
+
+/// Helper function to open the shuffle reveal window
+///
+/// The slot hash is mixed into the entropy before any secret is revealed, so it is fixed
+/// independently of when the host later starts the hand.
+fn open_reveal_window(table: &mut Table, recent_slot_hash: &[u8], now: i64) {
+    table.shuffle_entropy = hashv(&[&table.shuffle_entropy, recent_slot_hash]).to_bytes();
+    table.reveal_deadline = now.checked_add(SHUFFLE_REVEAL_WINDOW).unwrap();
+}
+
+/// Helper function to post an ante, capped at the player's stack
+///
+/// Antes go into the pot without counting toward the player's bet on the street.
+fn post_ante(player_state: &mut PlayerState, amount: u64) -> u64 {
+    let posted = std::cmp::min(amount, player_state.chips);
+    player_state.chips = player_state.chips.checked_sub(posted).unwrap();
+    player_state.current_bet = 0;
+    player_state.total_contributed = posted;
+    if player_state.chips == 0 {
+        player_state.is_all_in = true;
+    }
+    posted
+}
+
+/// Helper function to post a forced bet, capped at the player's stack
+fn post_blind(player_state: &mut PlayerState, amount: u64) -> u64 {
+    let posted = std::cmp::min(amount, player_state.chips);
+    player_state.chips = player_state.chips.checked_sub(posted).unwrap();
+    player_state.current_bet = posted;
+    player_state.total_contributed = player_state.total_contributed.checked_add(posted).unwrap();
+    if player_state.chips == 0 {
+        player_state.is_all_in = true;
+    }
+    posted
+}
+
+/// Helper function to check a tournament's blind schedule and payout table
+fn validate_tournament_config(config: &TournamentConfig, max_places: usize, variant: &GameVariant) -> Result<()> {
+    require!(config.starting_stack > 0, ErrorCode::InvalidStartingStack);
+    require!(
+        !config.blind_levels.is_empty() && config.blind_levels.len() <= MAX_BLIND_LEVELS,
+        ErrorCode::InvalidBlindSchedule
+    );
+    require!(config.level_duration > 0, ErrorCode::InvalidBlindSchedule);
+    for level in config.blind_levels.iter() {
+        require!(level.big_blind >= level.small_blind, ErrorCode::InvalidBlinds);
+        require!(level.big_blind > 0 || level.ante > 0, ErrorCode::InvalidBlinds);
+        require!(*variant != GameVariant::SevenCardStud || level.small_blind > 0, ErrorCode::InvalidBlinds);
+    }
+    
+    // Every paid place needs an entrant, and the shares must cover the whole pool
+    require!(
+        !config.payouts_bps.is_empty() && config.payouts_bps.len() <= max_places,
+        ErrorCode::InvalidPayoutTable
+    );
+    let total_bps: u32 = config.payouts_bps.iter().map(|&bps| bps as u32).sum();
+    require!(total_bps == PAYOUT_BPS_TOTAL as u32, ErrorCode::InvalidPayoutTable);
+    
+    Ok(())
+}
+
+/// Helper function to move a tournament to the blind level its schedule has reached
+///
+/// Levels last `level_duration` hands or seconds depending on `level_clock`; the last
+/// level stays in force once the schedule runs out.
+fn update_blind_level(table: &mut Table) -> Result<()> {
+    let clock = Clock::get()?;
+    if table.level_started_at == 0 {
+        table.blind_level = 0;
+        table.level_started_hand = 0;
+        table.level_started_at = clock.unix_timestamp;
+    }
+    
+    while (table.blind_level as usize) + 1 < table.blind_levels.len() {
+        let level_over = match table.level_clock {
+            LevelClock::Hands => table.hands_played - table.level_started_hand >= table.level_duration,
+            LevelClock::Time => clock.unix_timestamp - table.level_started_at >= table.level_duration as i64,
+        };
+        if !level_over {
+            break;
+        }
+        table.blind_level += 1;
+        table.level_started_hand = table.level_started_hand.checked_add(table.level_duration).unwrap();
+        table.level_started_at = table.level_started_at.checked_add(table.level_duration as i64).unwrap();
+    }
+    
+    let level = table.blind_levels[table.blind_level as usize];
+    table.small_blind = level.small_blind;
+    table.big_blind = level.big_blind;
+    table.ante = level.ante;
+    
+    Ok(())
+}
+
+/// Helper function to eliminate tournament players who busted in the last hand
+///
+/// Eliminated players give up their seat and keep their finishing place for `claim_prize`.
+/// Players busting in the same hand are placed by the stack they started it with. Once one
+/// player is left they have won and the tournament is complete.
+///
+/// During the rebuy period a busted player with rebuys left sits out instead, as long as two
+/// players still have chips to play on.
+fn eliminate_busted_players(table: &mut Table, player_states: &mut [PlayerState]) {
+    let seated: Vec<usize> = (0..table.players.len())
+        .filter(|&seat| table.players[seat] != Pubkey::default())
+        .collect();
+    let players_with_chips = seated.iter().filter(|&&seat| player_states[seat].chips > 0).count();
+    
+    let mut busted = Vec::new();
+    for &seat in seated.iter() {
+        let player_state = &mut player_states[seat];
+        if player_state.chips > 0 {
+            continue;
+        }
+        if players_with_chips >= 2 && can_rebuy(table, player_state) {
+            player_state.is_sitting_out = true;
+        } else {
+            busted.push(seat);
+        }
+    }
+    busted.sort_by_key(|&seat| player_states[seat].total_contributed);
+    
+    for seat in busted {
+        let place = table.player_count as u16;
+        eliminate_busted_player(table, &mut player_states[seat], place).unwrap();
+    }
+    
+    if table.player_count == 1 {
+        table.status = TableStatus::Completed;
+    }
+}
+
+/// Helper function to take a busted tournament player off their table in the given place
+///
+/// The place is the number of players left in the tournament, counting the player. Nobody
+/// is left to eliminate the winner, so they alone keep place 0 (see `tournament_place`).
+fn eliminate_busted_player(table: &mut Table, player_state: &mut PlayerState, place: u16) -> Result<()> {
+    unseat_player(table, player_state.player)?;
+    player_state.is_active = false;
+    player_state.finishing_place = place;
+    Ok(())
+}
+
+/// Helper function to get the place a tournament player finished in once play has stopped
+fn tournament_place(finishing_place: u16) -> usize {
+    if finishing_place == 0 {
+        1
+    } else {
+        finishing_place as usize
+    }
+}
+
+/// Helper function to fit a payout table to the number of entrants
+///
+/// Places beyond the field are dropped and the shares of those left are scaled back up to
+/// the whole pool, first place taking the rounding remainder.
+fn fit_payouts(payouts_bps: &[u16], entrants: usize) -> Vec<u16> {
+    let places = &payouts_bps[..std::cmp::min(payouts_bps.len(), entrants)];
+    let total_bps: u32 = places.iter().map(|&bps| bps as u32).sum();
+    if total_bps == 0 {
+        return vec![PAYOUT_BPS_TOTAL];
+    }
+    
+    let mut fitted: Vec<u16> = places
+        .iter()
+        .map(|&bps| (bps as u32 * PAYOUT_BPS_TOTAL as u32 / total_bps) as u16)
+        .collect();
+    let fitted_bps: u32 = fitted.iter().map(|&bps| bps as u32).sum();
+    fitted[0] = fitted[0].checked_add((PAYOUT_BPS_TOTAL as u32 - fitted_bps) as u16).unwrap();
+    fitted
+}
+
+/// Helper function to get the prize for a tournament finishing place
+///
+/// Each paid place gets its basis-point share of the pool rounded down, and the winner
+/// also takes the rounding remainder so the whole pool is paid out.
+fn prize_for_place(prize_pool: u64, payouts_bps: &[u16], place: usize) -> u64 {
+    if place == 0 || place > payouts_bps.len() {
+        return 0;
+    }
+    
+    let share = |bps: u16| (prize_pool as u128 * bps as u128 / PAYOUT_BPS_TOTAL as u128) as u64;
+    if place == 1 {
+        let other_places: u64 = payouts_bps[1..].iter().map(|&bps| share(bps)).sum();
+        prize_pool.checked_sub(other_places).unwrap()
+    } else {
+        share(payouts_bps[place - 1])
+    }
+}
+
+/// Helper function to remember the stack a cash player left with, for anti-ratholing
+///
+/// Departures only leave the list once they expire. `join_table` keeps room for every seated
+/// player, so there is always space for one more.
+fn record_departure(table: &mut Table, player: Pubkey, chips: u64, now: i64) {
+    table
+        .recent_departures
+        .retain(|departure| departure.player != player && now - departure.left_at < RATHOLE_WINDOW);
+    table.recent_departures.push(Departure { player, chips, left_at: now });
+}
+
+/// Helper function to take a returning player's departure if they left within the window
+///
+/// Returns the stack they left with, which they must bring back to rejoin. Expired departures
+/// are dropped along the way.
+fn take_recent_departure(table: &mut Table, player: &Pubkey, now: i64) -> Option<u64> {
+    table.recent_departures.retain(|departure| now - departure.left_at < RATHOLE_WINDOW);
+    let index = table.recent_departures.iter().position(|departure| departure.player == *player)?;
+    Some(table.recent_departures.remove(index).chips)
+}
+
+/// Helper function to transfer a rebuy or top-up from the player to the table vault
+fn deposit_chips(ctx: &Context<Rebuy>, amount: u64) -> Result<()> {
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.player_token_account.to_account_info(),
+        to: ctx.accounts.table_vault.to_account_info(),
+        authority: ctx.accounts.player.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, amount)
+}
+
+/// Helper function to move a tournament rebuy or add-on from the player to the tournament vault
+fn deposit_tournament_chips(ctx: &Context<TournamentRebuy>, amount: u64) -> Result<()> {
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.player_token_account.to_account_info(),
+        to: ctx.accounts.tournament_vault.to_account_info(),
+        authority: ctx.accounts.player.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, amount)
+}
+
+/// Helper function to check whether a tournament player still has a rebuy they can use
+fn can_rebuy(table: &Table, player_state: &PlayerState) -> bool {
+    table.blind_level < table.rebuy_levels && player_state.rebuys < table.max_rebuys
+}
+
+/// Helper function to check that a tournament player may rebuy between hands
+///
+/// Only players at or below the starting stack may rebuy.
+fn require_rebuy_open(table: &Table, player_state: &PlayerState) -> Result<()> {
+    require!(table.status != TableStatus::Playing, ErrorCode::GameInProgress);
+    require!(player_state.is_active, ErrorCode::PlayerNotActive);
+    require!(table.commit_count == 0, ErrorCode::ShuffleInProgress);
+    require!(table.blind_level < table.rebuy_levels, ErrorCode::RebuyNotAllowed);
+    require!(player_state.rebuys < table.max_rebuys, ErrorCode::RebuyLimitReached);
+    require!(player_state.chips <= table.starting_stack, ErrorCode::StackLimitExceeded);
+    
+    Ok(())
+}
+
+/// Helper function to check that a tournament player may take the add-on between hands
+fn require_add_on_open(table: &Table, player_state: &PlayerState) -> Result<()> {
+    require!(table.status != TableStatus::Playing, ErrorCode::GameInProgress);
+    require!(player_state.is_active && player_state.chips > 0, ErrorCode::PlayerNotActive);
+    require!(
+        table.add_on_chips > 0 && table.rebuy_levels > 0 && table.blind_level == table.rebuy_levels,
+        ErrorCode::RebuyNotAllowed
+    );
+    require!(!player_state.add_on_taken, ErrorCode::RebuyLimitReached);
+    
+    Ok(())
+}
+
+/// Helper function to give a player the starting stack they rebought; a busted player sits
+/// back in
+fn credit_rebuy(table: &Table, player_state: &mut PlayerState) {
+    if player_state.chips == 0 {
+        player_state.is_sitting_out = false;
+    }
+    player_state.chips = player_state.chips.checked_add(table.starting_stack).unwrap();
+    player_state.rebuys = player_state.rebuys.checked_add(1).unwrap();
+}
+
+/// Helper function to find a table's index among a tournament's active tables
+fn tournament_table_index(tournament: &Tournament, table: &Pubkey) -> Result<usize> {
+    let index = tournament.tables.iter().position(|key| key == table);
+    require!(index.is_some(), ErrorCode::TableNotInTournament);
+    Ok(index.unwrap())
+}
+
+/// Helper function to count a player moving between two tables of a tournament
+///
+/// Players only leave a live table for one with at least two fewer players. A player leaving
+/// a broken table, passed as `None`, was already taken off its count.
+fn move_table_count(tournament: &mut Tournament, from_table: Option<&Pubkey>, to_table: &Pubkey) -> Result<()> {
+    let to_index = tournament_table_index(tournament, to_table)?;
+    if let Some(from_table) = from_table {
+        let from_index = tournament_table_index(tournament, from_table)?;
+        require!(
+            tournament.table_counts[from_index] >= tournament.table_counts[to_index].checked_add(2).unwrap(),
+            ErrorCode::TablesUnbalanced
+        );
+        tournament.table_counts[from_index] = tournament.table_counts[from_index].checked_sub(1).unwrap();
+    }
+    tournament.table_counts[to_index] = tournament.table_counts[to_index].checked_add(1).unwrap();
+    Ok(())
+}
+
+/// Helper function to take a table being broken out of a tournament
+///
+/// The players must fit in the remaining tables, and the table broken is one with the fewest
+/// players. Once a single table is left it becomes the final table.
+fn remove_tournament_table(tournament: &mut Tournament, table: &Pubkey) -> Result<()> {
+    let table_index = tournament_table_index(tournament, table)?;
+    let remaining_seats = (tournament.tables.len() - 1) * tournament.seats_per_table as usize;
+    require!(tournament.players_remaining as usize <= remaining_seats, ErrorCode::TablesUnbalanced);
+    let fewest_players = tournament.table_counts.iter().copied().min().unwrap();
+    require!(tournament.table_counts[table_index] == fewest_players, ErrorCode::TablesUnbalanced);
+    
+    tournament.tables.remove(table_index);
+    tournament.table_counts.remove(table_index);
+    if tournament.tables.len() == 1 {
+        tournament.final_table = tournament.tables[0];
+    }
+    Ok(())
+}
+
+/// Helper function to escrow a new table's crank bounty from the account creating it
+fn escrow_crank_bounty<'info>(
+    payer: &Signer<'info>,
+    table: &mut Account<'info, Table>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let cpi_accounts = anchor_lang::system_program::Transfer {
+        from: payer.to_account_info(),
+        to: table.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+    anchor_lang::system_program::transfer(cpi_ctx, CRANK_BOUNTY_ESCROW)?;
+    table.crank_bounty = CRANK_BOUNTY_ESCROW;
+    Ok(())
+}
+
+/// Helper function to seat a player in the first empty seat of a table
+fn seat_player(table: &mut Table, player: Pubkey) -> Result<usize> {
+    let seat = table.players.iter().position(|player_pubkey| *player_pubkey == Pubkey::default());
+    require!(seat.is_some() && table.player_count < table.max_players, ErrorCode::TableFull);
+    
+    let seat = seat.unwrap();
+    table.players[seat] = player;
+    table.player_count = table.player_count.checked_add(1).unwrap();
+    Ok(seat)
+}
+
+/// Helper function to free a player's seat at a table
+fn unseat_player(table: &mut Table, player: Pubkey) -> Result<usize> {
+    let seat = table.players.iter().position(|player_pubkey| *player_pubkey == player);
+    require!(seat.is_some(), ErrorCode::PlayerNotAtTable);
+    
+    let seat = seat.unwrap();
+    table.players[seat] = Pubkey::default();
+    table.player_count = table.player_count.checked_sub(1).unwrap();
+    Ok(seat)
+}
+
+/// Interface a VRF oracle program must implement to serve randomness to tables
+///
+/// The oracle accepts `request_randomness` (signed by the requesting table PDA) and later calls
+/// this program's `fulfill_randomness`, signing with its `ORACLE_AUTHORITY_SEED` PDA. Any program
+/// following this layout can be plugged in, including a mock oracle on a local validator.
+pub mod vrf_oracle {
+    use super::*;
+    use anchor_lang::solana_program::hash::hash;
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+
+    /// Seed of the PDA the oracle program signs fulfilments with
+    pub const ORACLE_AUTHORITY_SEED: &[u8] = b"oracle_authority";
+
+    /// Address the given oracle program signs fulfilments from
+    pub fn oracle_authority(oracle_program: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[ORACLE_AUTHORITY_SEED], oracle_program).0
+    }
+
+    /// Size of the oracle's result account: an 8-byte discriminator, then the requester, the
+    /// request id, the 80-byte VRF proof, and the randomness
+    pub const RESULT_LEN: usize = 8 + 32 + 32 + 80 + 32;
+
+    /// Read a fulfilled request from the oracle's result account
+    ///
+    /// The randomness must be the hash of the published proof. This only ties the two together
+    /// so the proof can be checked against the oracle's public key off-chain; the proof itself
+    /// is not verified here.
+    pub fn read_result(data: &[u8]) -> Option<(Pubkey, [u8; 32], [u8; 32])> {
+        if data.len() < RESULT_LEN {
+            return None;
+        }
+        
+        let requester = Pubkey::new_from_array(data[8..40].try_into().unwrap());
+        let request_id: [u8; 32] = data[40..72].try_into().unwrap();
+        let proof = &data[72..152];
+        let randomness: [u8; 32] = data[152..184].try_into().unwrap();
+        if hash(proof).to_bytes() != randomness {
+            return None;
+        }
+        
+        Some((requester, request_id, randomness))
+    }
+
+    /// Build the oracle's `request_randomness(request_id, callback_program)` instruction
+    pub fn request_randomness_ix(
+        oracle_program: &Pubkey,
+        requester: &Pubkey,
+        oracle_state: &Pubkey,
+        request_id: [u8; 32],
+    ) -> Instruction {
+        let mut data = hash(b"global:request_randomness").to_bytes()[..8].to_vec();
+        data.extend_from_slice(&request_id);
+        data.extend_from_slice(crate::ID.as_ref());
+        
+        Instruction {
+            program_id: *oracle_program,
+            accounts: vec![
+                AccountMeta::new_readonly(*requester, true),
+                AccountMeta::new(*oracle_state, false),
+            ],
+            data,
+        }
+    }
+}
+
+/// Helper function to start a hand from a deck seed: deal cards, post blinds and open the
+/// first betting round
+fn deal_hand(table: &mut Table, player_states: &mut [PlayerState], seed: u64) -> Result<()> {
+    // Only occupied seats that are not sitting out take part in the hand; players may sit
+    // out while an oracle request is pending, so this is checked again here
+    let participating: Vec<bool> = table
+        .players
+        .iter()
+        .enumerate()
+        .map(|(i, player_pubkey)| *player_pubkey != Pubkey::default() && !player_states[i].is_sitting_out)
+        .collect();
+    let participant_count = participating.iter().filter(|&&seat| seat).count();
+    require!(participant_count >= 2, ErrorCode::NotEnoughPlayers);
+    
+    // Tournament blinds rise on their schedule between hands
+    if table.game_mode != GameMode::CashGame {
+        update_blind_level(table)?;
+    }
+    table.hands_played = table.hands_played.checked_add(1).unwrap();
+    
+    // Time banks are topped back up every `time_bank_refill_hands` hands
+    let refill_due = table.hands_played.checked_rem(table.time_bank_refill_hands) == Some(0);
+    
+    // Update table status
+    table.status = TableStatus::Playing;
+    table.round = match table.game_variant {
+        GameVariant::FiveCardDraw => Round::PreDraw,
+        GameVariant::SevenCardStud => Round::ThirdStreet,
+        _ => Round::PreFlop,
+    };
+    
+    // Set dealer position (randomized based on seed) among the participating seats
+    let dealer_offset = (seed % participant_count as u64) as usize;
+    let dealer_seat = (0..participating.len())
+        .filter(|&seat| participating[seat])
+        .nth(dealer_offset)
+        .unwrap();
+    table.dealer_index = dealer_seat as u8;
+    
+    // Calculate small blind and big blind positions; heads-up the dealer posts the small blind
+    let sb_seat = if participant_count == 2 {
+        dealer_seat
+    } else {
+        next_seat(&participating, dealer_seat).unwrap()
+    };
+    let bb_seat = next_seat(&participating, sb_seat).unwrap();
+    
+    // The seed permutes positions of the dealer's committed deck rather than cards themselves
+    let deck = generate_shuffled_deck(seed, deck_cards(&table.game_variant).len());
+    
+    // Assign each participating player the variant's number of hole card positions
+    let hole_cards = hole_card_count(&table.game_variant);
+    let mut card_index = 0;
+    for (i, player_pubkey) in table.players.iter().enumerate() {
+        if *player_pubkey != Pubkey::default() {
+            // Players sitting out take no part in this hand
+            if player_states[i].is_sitting_out {
+                player_states[i].is_folded = true;
+                continue;
+            }
+            
+            let player_state = &mut player_states[i];
+            
+            // Only the dealer knows which cards sit at these positions until they are revealed
+            player_state.hole_positions = [0; MAX_HOLE_CARDS];
+            player_state.hole_positions[..hole_cards].copy_from_slice(&deck[card_index..card_index + hole_cards]);
+            player_state.cards_revealed = false;
+            player_state.upcards = [0; STUD_UPCARDS];
+            player_state.upcards_revealed = 0;
+            player_state.has_acted = false;
+            player_state.last_action_bet = 0;
+            if refill_due {
+                refill_time_bank(table, player_state);
+            }
+            card_index += hole_cards;
+        }
+    }
+    
+    // Store community card positions for later reveals
+    table.community_revealed = 0;
+    table.community_positions = [0; 5];
+    let board_cards = board_card_count(&table.game_variant);
+    table.community_positions[..board_cards].copy_from_slice(&deck[card_index..card_index + board_cards]);
+    card_index += board_cards;
+    
+    // Remember where the undealt cards start so draws can be dealt from the same deck
+    table.deck_seed = seed;
+    table.deck_position = card_index as u8;
+    
+    // Every participant antes first; antes are dead money and do not count toward calling
+    table.pot = 0;
+    for (seat, &participates) in participating.iter().enumerate() {
+        if participates && table.ante > 0 {
+            let posted = post_ante(&mut player_states[seat], table.ante);
+            table.pot = table.pot.checked_add(posted).unwrap();
+        }
+    }
+    
+    // Initialize game state
+    table.highest_bet = 0;
+    table.bets_this_street = 0;
+    table.last_raise_size = min_bet_for_street(table);
+    
+    // Ante-only tables post no blinds and action starts left of the dealer; stud posts no blinds
+    // either, as its bring-in waits for the third street upcards
+    let mut last_forced_seat = dealer_seat;
+    if table.big_blind > 0 && table.game_variant != GameVariant::SevenCardStud {
+        // Post blinds; a short stack posts what it has and is all-in. Posting a blind is not an
+        // action, so the big blind still gets its option once the action comes back around
+        let sb_amount = post_blind(&mut player_states[sb_seat], table.small_blind);
+        let bb_amount = post_blind(&mut player_states[bb_seat], table.big_blind);
+        
+        // Update pot
+        table.pot = table.pot.checked_add(sb_amount).unwrap().checked_add(bb_amount).unwrap();
+        
+        // The big blind counts as the opening bet for the minimum raise
+        table.highest_bet = std::cmp::max(sb_amount, bb_amount);
+        table.bets_this_street = 1;
+        last_forced_seat = bb_seat;
+    }
+    
+    // Set current player to the first one after the big blind (or dealer) who can act
+    let can_act = seats_able_to_act(table, player_states);
+    table.current_player_index = next_seat(&can_act, last_forced_seat).unwrap_or(last_forced_seat) as u8;
+    table.turn_started_at = Clock::get()?.unix_timestamp;
+    table.dealer_deadline = table.turn_started_at.checked_add(DEALER_REVEAL_WINDOW).unwrap();
+    
+    // If the blinds left nobody to bet against, the board is simply run out
+    if betting_closed(table, player_states) {
+        check_round_completion(table, player_states)?;
+    }
+    
+    Ok(())
+}
+
+/// Player states of every seat at a table, loaded from the instruction's remaining accounts
+///
+/// Instructions that look at the whole table take one player state account per occupied
+/// seat, in seat order. Empty seats hold a blank state so helpers can index by seat, and
+/// `save` writes the loaded accounts back once the instruction is done with them.
+struct Seats<'info> {
+    states: Vec<PlayerState>,
+    accounts: Vec<Option<AccountInfo<'info>>>,
+}
+
+impl<'info> Seats<'info> {
+    /// Load and check the player state of every occupied seat of `table`
+    fn load(table: &Account<'_, Table>, remaining_accounts: &[AccountInfo<'info>]) -> Result<Self> {
+        let mut remaining = remaining_accounts.iter();
+        let mut seats = Seats { states: Vec::new(), accounts: Vec::new() };
+        for player_pubkey in table.players.iter() {
+            if *player_pubkey == Pubkey::default() {
+                seats.states.push(PlayerState::default());
+                seats.accounts.push(None);
+                continue;
+            }
+            
+            // Each account must be this program's state for the player in this seat
+            let account = remaining.next().ok_or(ErrorCode::MissingPlayerState)?;
+            require!(account.owner == &crate::ID, ErrorCode::MissingPlayerState);
+            let player_state = PlayerState::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+            require!(
+                player_state.table == table.key() && player_state.player == *player_pubkey,
+                ErrorCode::PlayerNotAtTable
+            );
+            
+            seats.states.push(player_state);
+            seats.accounts.push(Some(account.clone()));
+        }
+        
+        Ok(seats)
+    }
+
+    /// Write every loaded player state back to its account
+    fn save(&self) -> Result<()> {
+        for (player_state, account) in self.states.iter().zip(self.accounts.iter()) {
+            if let Some(account) = account {
+                let mut data = account.try_borrow_mut_data()?;
+                let mut writer: &mut [u8] = &mut data;
+                player_state.try_serialize(&mut writer)?;
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+impl<'info> std::ops::Deref for Seats<'info> {
+    type Target = [PlayerState];
+
+    fn deref(&self) -> &[PlayerState] {
+        &self.states
+    }
+}
+
+impl<'info> std::ops::DerefMut for Seats<'info> {
+    fn deref_mut(&mut self) -> &mut [PlayerState] {
+        &mut self.states
+    }
+}
+
+/// Helper function to flag the seats whose players can still act in the current hand
+///
+/// Empty seats and players who have left, folded or gone all-in are skipped.
+fn seats_able_to_act(table: &Table, player_states: &[PlayerState]) -> Vec<bool> {
+    table
+        .players
+        .iter()
+        .enumerate()
+        .map(|(i, player_pubkey)| {
+            if *player_pubkey == Pubkey::default() {
+                return false;
+            }
+            let player_state = &player_states[i];
+            player_state.is_active && !player_state.is_folded && !player_state.is_all_in
+        })
+        .collect()
+}
+
+/// Helper function to flag the seats whose players still have to draw
+///
+/// All-in players draw too, since only folding gives up a hand.
+fn seats_to_draw(table: &Table, player_states: &[PlayerState]) -> Vec<bool> {
+    table
+        .players
+        .iter()
+        .enumerate()
+        .map(|(i, player_pubkey)| {
+            if *player_pubkey == Pubkey::default() {
+                return false;
+            }
+            let player_state = &player_states[i];
+            player_state.is_active && !player_state.is_folded && !player_state.has_acted
+        })
+        .collect()
+}
+
+/// Helper function to replace the hole cards picked out by `discard_mask`
+///
+/// Replacements are dealt in order from the deck positions nobody has received yet. A player
+/// who draws needs their new hand delivered again, so their encrypted cards are cleared.
+fn draw_replacements(table: &mut Table, player_state: &mut PlayerState, discard_mask: u8) {
+    let deck = generate_shuffled_deck(table.deck_seed, deck_cards(&table.game_variant).len());
+    for i in 0..hole_card_count(&table.game_variant) {
+        if discard_mask & (1 << i) != 0 {
+            player_state.hole_positions[i] = deck[table.deck_position as usize];
+            table.deck_position = table.deck_position.checked_add(1).unwrap();
+        }
+    }
+    if discard_mask != 0 {
+        player_state.encrypted_cards = Vec::new();
+    }
+}
+
+/// Helper function to post the stud bring-in from the player showing the lowest upcard
+///
+/// Equal ranks are broken by suit order. Posting the bring-in is not an action, so the player
+/// can still complete the bet once the action comes back around.
+fn post_bring_in(table: &mut Table, player_states: &mut [PlayerState]) {
+    let can_act = seats_able_to_act(table, player_states);
+    let bring_in_seat = (0..can_act.len())
+        .filter(|&seat| can_act[seat])
+        .min_by_key(|&seat| {
+            let upcard = player_states[seat].upcards[0];
+            (card_rank(upcard), card_suit(upcard))
+        })
+        .unwrap();
+    
+    let posted = post_blind(&mut player_states[bring_in_seat], table.small_blind);
+    table.pot = table.pot.checked_add(posted).unwrap();
+    table.highest_bet = posted;
+    table.current_player_index = next_seat(&can_act, bring_in_seat).unwrap() as u8;
+}
+
+/// Helper function to find who opens a stud street: the live player with the best visible hand
+///
+/// Ties go to the player closest to the dealer's left. If that player is all-in, the action
+/// starts with the next player who can act.
+fn stud_opener(table: &Table, player_states: &[PlayerState]) -> Option<usize> {
+    let seats = table.players.len();
+    let mut best: Option<(u32, usize)> = None;
+    for offset in 1..=seats {
+        let seat = (table.dealer_index as usize + offset) % seats;
+        if table.players[seat] == Pubkey::default() {
+            continue;
+        }
+        
+        let player_state = &player_states[seat];
+        if !player_state.is_active || player_state.is_folded {
+            continue;
+        }
+        let upcards = &player_state.upcards[..player_state.upcards_revealed as usize];
+        let value = evaluate_poker_hand(upcards, false);
+        if best.is_none() || best.is_some_and(|(best_value, _)| value > best_value) {
+            best = Some((value, seat));
+        }
+    }
+    
+    let (_, opener) = best?;
+    let can_act = seats_able_to_act(table, player_states);
+    next_seat(&can_act, (opener + seats - 1) % seats)
+}
+
+/// Helper function to pass the draw to the next player, or open the final betting round once
+/// everyone has drawn
+fn finish_draw(table: &mut Table, player_states: &mut [PlayerState]) -> Result<()> {
+    let to_draw = seats_to_draw(table, player_states);
+    if let Some(seat) = next_seat(&to_draw, table.current_player_index as usize) {
+        table.current_player_index = seat as u8;
+    } else {
+        advance_round(table, player_states)?;
+        
+        // With nobody left to bet against, go straight to showdown
+        if betting_closed(table, player_states) {
+            advance_round(table, player_states)?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Helper function to find the first flagged seat clockwise after `from`
+///
+/// Walks every seat of the table, so `from` itself is returned last if it is the only match.
+fn next_seat(flags: &[bool], from: usize) -> Option<usize> {
+    let seats = flags.len();
+    (1..=seats)
+        .map(|offset| (from + offset) % seats)
+        .find(|&seat| flags[seat])
+}
+
+/// Helper function to advance to the next player who can act
+fn advance_to_next_player(table: &mut Table, player_states: &[PlayerState]) -> Result<()> {
+    let can_act = seats_able_to_act(table, player_states);
+    if let Some(seat) = next_seat(&can_act, table.current_player_index as usize) {
+        table.current_player_index = seat as u8;
+    }
+    
+    Ok(())
+}
+
+/// Helper function to check if the current betting round is complete
+///
+/// A street ends only when every player who can still act has acted since the last raise
+/// and matched the highest bet, so the big blind keeps its option and checks go around.
+/// When nobody is left to bet against, matching the bet is enough.
+fn check_round_completion(table: &mut Table, player_states: &mut [PlayerState]) -> Result<()> {
+    let can_act = seats_able_to_act(table, player_states);
+    let all_matched = can_act
+        .iter()
+        .enumerate()
+        .filter(|&(_, &able)| able)
+        .all(|(i, _)| player_states[i].current_bet >= table.highest_bet);
+    let all_acted = can_act
+        .iter()
+        .enumerate()
+        .filter(|&(_, &able)| able)
+        .all(|(i, _)| player_states[i].has_acted);
+    let round_complete = all_matched && (all_acted || betting_closed(table, player_states));
+    
+    if round_complete {
+        advance_round(table, player_states)?;
+    }
+    
+    Ok(())
+}
+
+/// Helper function to close the current betting round and move to the next street
+fn advance_round(table: &mut Table, player_states: &mut [PlayerState]) -> Result<()> {
+    // Reset bets and actions for next round
+    for player_state in player_states.iter_mut() {
+        player_state.current_bet = 0;
+        player_state.has_acted = false;
+        player_state.last_action_bet = 0;
+    }
+    
+    table.highest_bet = 0;
+    table.bets_this_street = 0;
+    
+    // Advance to next round
+    match table.round {
+        Round::PreFlop => {
+            table.round = Round::Flop;
+            // Betting resumes once the dealer reveals the flop with `reveal_street`
+        }
+        Round::Flop => {
+            table.round = Round::Turn;
+        }
+        Round::Turn => {
+            table.round = Round::River;
+        }
+        Round::PreDraw => {
+            table.round = Round::Draw;
+        }
+        Round::Draw => {
+            table.round = Round::PostDraw;
+        }
+        Round::ThirdStreet => {
+            table.round = Round::FourthStreet;
+            // Stud betting resumes once the dealer reveals the street's upcards with `reveal_upcards`
+        }
+        Round::FourthStreet => {
+            table.round = Round::FifthStreet;
+        }
+        Round::FifthStreet => {
+            table.round = Round::SixthStreet;
+        }
+        Round::SixthStreet => {
+            table.round = Round::SeventhStreet;
+        }
+        Round::River | Round::PostDraw | Round::SeventhStreet => {
+            table.round = Round::Showdown;
+            
+            // Live players get a window to open their hole cards
+            let clock = Clock::get()?;
+            table.showdown_deadline = clock.unix_timestamp.checked_add(SHOWDOWN_REVEAL_WINDOW).unwrap();
+        }
+        _ => {}
+    }
+    
+    // The dealer has a window to reveal the cards opening the new street
+    table.dealer_deadline = Clock::get()?.unix_timestamp.checked_add(DEALER_REVEAL_WINDOW).unwrap();
+    table.last_raise_size = min_bet_for_street(table);
+    
+    // Action starts from the first player left of the dealer who can still act, or who still
+    // has to draw
+    let can_act = if table.round == Round::Draw {
+        seats_to_draw(table, player_states)
+    } else {
+        seats_able_to_act(table, player_states)
+    };
+    if let Some(seat) = next_seat(&can_act, table.dealer_index as usize) {
+        table.current_player_index = seat as u8;
+    }
+    
+    // Stud action follows the visible cards instead, and seventh street is dealt face down, so
+    // with nobody left to bet against it goes straight to showdown
+    if table.game_variant == GameVariant::SevenCardStud && table.round != Round::Showdown {
+        if let Some(seat) = stud_opener(table, player_states) {
+            table.current_player_index = seat as u8;
+        }
+        if table.round == Round::SeventhStreet && betting_closed(table, player_states) {
+            return advance_round(table, player_states);
+        }
+    }
+    
+    Ok(())
+}
+
+/// Helper function to get the table's basic betting unit: the big blind, or the ante on
+/// ante-only tables
+fn betting_unit(table: &Table) -> u64 {
+    std::cmp::max(table.big_blind, table.ante)
+}
+
+/// Helper function to get the smallest opening bet on the current street
+///
+/// Fixed-limit tables bet in small bets (one unit) pre-flop and on the flop, and in
+/// big bets (two units) on the turn and river. Draw games use big bets after the draw and
+/// stud from fifth street on.
+fn min_bet_for_street(table: &Table) -> u64 {
+    match (&table.betting_structure, &table.round) {
+        (
+            BettingStructure::FixedLimit,
+            Round::Turn
+            | Round::River
+            | Round::PostDraw
+            | Round::FifthStreet
+            | Round::SixthStreet
+            | Round::SeventhStreet,
+        ) => {
+            betting_unit(table).checked_mul(2).unwrap()
+        }
+        _ => betting_unit(table),
+    }
+}
+
+/// Helper function to require every other player to act again after a raise
+fn reopen_action(player_states: &mut [PlayerState], raiser_seat: usize) {
+    for (i, player_state) in player_states.iter_mut().enumerate() {
+        if i != raiser_seat {
+            player_state.has_acted = false;
+        }
+    }
+}
+
+/// Helper function to check whether a player may raise
+///
+/// Once a player has acted they may only raise again after a full raise, or short all-ins
+/// adding up to one, lifts the bet a full raise above what they last faced.
+fn betting_reopened(table: &Table, player_state: &PlayerState) -> bool {
+    !player_state.has_acted
+        || table.highest_bet >= player_state.last_action_bet.checked_add(table.last_raise_size).unwrap()
+}
+
+/// Helper function to record a raise to `amount` by `raise_size` on the table
+///
+/// A full raise means everyone else must act again, while an incomplete all-in raise only
+/// obliges them to call or fold.
+fn record_raise(
+    table: &mut Table,
+    player_states: &mut [PlayerState],
+    raiser_seat: usize,
+    amount: u64,
+    raise_size: u64,
+) {
+    if raise_size >= table.last_raise_size {
+        table.last_raise_size = raise_size;
+        table.bets_this_street = table.bets_this_street.checked_add(1).unwrap();
+        reopen_action(player_states, raiser_seat);
+    }
+    table.highest_bet = amount;
+}
+
+/// Helper function to check whether any betting is possible on the current street
+///
+/// With at most one player able to act there is nobody left to bet against, so the
+/// remaining streets are simply run out.
+fn betting_closed(table: &Table, player_states: &[PlayerState]) -> bool {
+    seats_able_to_act(table, player_states).iter().filter(|&&able| able).count() <= 1
+}
+
+/// Helper function to give the whole pot to the last player left in the hand and end it
+///
+/// The part of the winner's bet nobody called is handed back before the pot is raked.
+fn award_uncontested_pot(table: &mut Table, player_states: &mut [PlayerState]) -> Result<()> {
+    let contributions: Vec<(usize, u64, bool)> = player_states
+        .iter()
+        .enumerate()
+        .map(|(i, player_state)| (i, player_state.total_contributed, player_state.is_active && !player_state.is_folded))
+        .collect();
+    if let Some((seat, excess)) = uncalled_excess(&contributions) {
+        let player_state = &mut player_states[seat];
+        player_state.total_contributed = player_state.total_contributed.checked_sub(excess).unwrap();
+        player_state.chips = player_state.chips.checked_add(excess).unwrap();
+        table.pot = table.pot.checked_sub(excess).unwrap();
+    }
+    
+    let amount = table.pot.checked_sub(take_rake(table, table.pot)).unwrap();
+    for player_state in player_states.iter_mut() {
+        if player_state.is_active && !player_state.is_folded {
+            player_state.chips = player_state.chips.checked_add(amount).unwrap();
+            break;
+        }
+    }
+    
+    // End the game
+    table.status = TableStatus::Finished;
+    table.finished_at = Clock::get()?.unix_timestamp;
+    
+    Ok(())
+}
+
+/// Helper function to check that the host is moving the table on, or that a tournament
+/// table has waited long enough for them
+///
+/// The host is one of a tournament's entrants and may bust or drop out, so there anyone may
+/// act in their place once `HOST_GRACE_PERIOD` has passed after `deadline`.
+fn require_host_or_expired(table: &Table, caller: &Pubkey, deadline: i64, now: i64) -> Result<()> {
+    if *caller == table.host {
+        return Ok(());
+    }
+    
+    require!(table.game_mode != GameMode::CashGame, ErrorCode::NotTableHost);
+    require!(now > deadline.checked_add(HOST_GRACE_PERIOD).unwrap(), ErrorCode::HostWindowOpen);
+    
+    Ok(())
+}
+
+/// Helper function to read a game authority account still in the first release's layout
+fn read_legacy_game_authority(data: &[u8]) -> Result<LegacyGameAuthority> {
+    require!(data.len() == 8 + LegacyGameAuthority::SIZE, ErrorCode::AlreadyMigrated);
+    require!(data[..8] == <GameAuthority as anchor_lang::Discriminator>::DISCRIMINATOR, ErrorCode::InvalidGameAuthority);
+    Ok(LegacyGameAuthority::deserialize(&mut &data[8..])?)
+}
+
+/// Helper function to carry a first-release game authority over to the current layout
+///
+/// Its authority, fee and statistics are kept; every newer setting starts out as `initialize`
+/// leaves it.
+fn migrated_game_authority(legacy: &LegacyGameAuthority, fee_mint: Pubkey) -> GameAuthority {
+    GameAuthority {
+        authority: legacy.authority,
+        fee_percentage: legacy.fee_percentage,
+        total_games_played: legacy.total_games_played,
+        total_fees_collected: legacy.total_fees_collected,
+        rake_cap_big_blinds: 0,
+        protocol_fee_bps: 10_000,
+        host_fee_bps: 0,
+        referral_fee_bps: 0,
+        protocol_fees: 0,
+        referral_fees: 0,
+        pending_authority: Pubkey::default(),
+        signers: Vec::new(),
+        threshold: 0,
+        paused: false,
+        pause_scope: 0,
+        oracles: Vec::new(),
+        dealers: Vec::new(),
+        fee_mint,
+        bump: legacy.bump,
+    }
+}
+
+/// Helper function to check that an authority action is approved
+///
+/// Without a signer set the authority alone decides. Otherwise at least `threshold` distinct
+/// members of the set must sign, as `authority` or among the remaining accounts.
+fn verify_authority<'info>(
+    game_authority: &GameAuthority,
+    authority: &Signer<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    if game_authority.signers.is_empty() {
+        require!(authority.key() == game_authority.authority, ErrorCode::Unauthorized);
+        return Ok(());
+    }
+    
+    let mut approvals: Vec<Pubkey> = Vec::new();
+    for account in std::iter::once(authority.to_account_info()).chain(remaining_accounts.iter().cloned()) {
+        if account.is_signer && game_authority.signers.contains(account.key) && !approvals.contains(account.key) {
+            approvals.push(*account.key);
+        }
+    }
+    require!(approvals.len() >= game_authority.threshold as usize, ErrorCode::Unauthorized);
+    
+    Ok(())
+}
+
+/// Helper function to check a pause scope: only `PAUSE_*` flags, and at least one while paused
+fn validate_pause_scope(paused: bool, pause_scope: u8) -> Result<()> {
+    require!(pause_scope & !PAUSE_ALL == 0, ErrorCode::InvalidPauseScope);
+    require!(!paused || pause_scope != 0, ErrorCode::InvalidPauseScope);
+    Ok(())
+}
+
+/// Helper function to refuse an action the authority has paused
+fn require_not_paused(game_authority: &GameAuthority, scope: u8) -> Result<()> {
+    require!(!game_authority.paused || game_authority.pause_scope & scope == 0, ErrorCode::Paused);
+    Ok(())
+}
+
+/// Helper function to take the rake from a pot being settled, returning the amount taken
+///
+/// Only cash tables are raked, and a hand that ends in its first betting round is not ("no
+/// flop, no drop"). The hand's total rake stays within `rake_cap_big_blinds` betting units.
+fn take_rake(table: &mut Table, pot: u64) -> u64 {
+    let first_round = matches!(table.round, Round::PreFlop | Round::PreDraw | Round::ThirdStreet);
+    if table.game_mode != GameMode::CashGame || first_round {
+        return 0;
+    }
+    
+    let mut rake = pot.checked_mul(table.rake_percentage as u64).unwrap() / 100;
+    if table.rake_cap_big_blinds > 0 {
+        let cap = betting_unit(table).checked_mul(table.rake_cap_big_blinds as u64).unwrap();
+        rake = std::cmp::min(rake, cap.saturating_sub(table.rake));
+    }
+    
+    // The host's share is owed to whoever hosted this hand, so it is set aside as it is taken
+    let host_share = rake.checked_mul(table.host_fee_bps as u64).unwrap() / 10_000;
+    table.rake = table.rake.checked_add(rake).unwrap();
+    table.host_rake_due = table.host_rake_due.checked_add(host_share).unwrap();
+    table.rake_due = table.rake_due.checked_add(rake.checked_sub(host_share).unwrap()).unwrap();
+    rake
+}
+
+/// Helper function to take the timeout crank reward out of a stalled player's stack
+///
+/// Tournament chips are not backed one-to-one by the vault, so only cash tables pay. A player
+/// still in the hand whom the reward leaves without chips plays on all-in.
+fn take_timeout_reward(table: &Table, player_state: &mut PlayerState) -> u64 {
+    if table.game_mode != GameMode::CashGame {
+        return 0;
+    }
+    
+    let reward = std::cmp::min(
+        betting_unit(table).checked_mul(TIMEOUT_CRANK_REWARD_BPS).unwrap() / 10_000,
+        player_state.chips,
+    );
+    player_state.chips = player_state.chips.checked_sub(reward).unwrap();
+    if reward > 0 && player_state.chips == 0 && !player_state.is_folded {
+        player_state.is_all_in = true;
+    }
+    reward
+}
+
+/// Helper function to take the payment for a timeout crank out of the table's crank bounty
+///
+/// Each crank is paid `TIMEOUT_CRANK_BOUNTY` lamports, or whatever is left once the bounty runs
+/// low.
+fn take_crank_bounty(table: &mut Table) -> u64 {
+    let bounty = std::cmp::min(TIMEOUT_CRANK_BOUNTY, table.crank_bounty);
+    table.crank_bounty = table.crank_bounty.checked_sub(bounty).unwrap();
+    bounty
+}
+
+/// Helper function to charge a player's time bank for any time taken past the action window
+fn charge_time_bank(table: &Table, player_state: &mut PlayerState, now: i64) {
+    let overtime = now - table.turn_started_at - table.action_timeout;
+    if overtime > 0 {
+        player_state.time_bank = std::cmp::max(player_state.time_bank - overtime, 0);
+    }
+}
+
+/// Helper function to add the table's refill to a player's time bank, up to its starting size
+fn refill_time_bank(table: &Table, player_state: &mut PlayerState) {
+    player_state.time_bank = std::cmp::min(
+        player_state.time_bank.checked_add(table.time_bank_refill).unwrap(),
+        table.time_bank,
+    );
+}
+
+/// Helper function to count active players who haven't folded
+fn count_active_players(table: &Table, player_states: &[PlayerState]) -> usize {
+    let mut count = 0;
+    
+    for (i, player_pubkey) in table.players.iter().enumerate() {
+        if *player_pubkey == Pubkey::default() {
+            continue;
+        }
+        
+        let player_state = &player_states[i];
+        if !player_state.is_folded && player_state.is_active {
+            count += 1;
+        }
+    }
+    
+    count
+}
+
+/// Helper function to split contributions into a main pot followed by layered side pots
+///
+/// Each entry is `(seat, total_contributed, still_in_hand)`. Folded players' chips are
+/// counted in every pot they reached but they are never eligible to win one.
+fn build_pots(contributions: &[(usize, u64, bool)]) -> Vec<Pot> {
+    // Every distinct all-in level among live players caps a pot
+    let mut levels: Vec<u64> = contributions
+        .iter()
+        .filter(|&&(_, amount, in_hand)| in_hand && amount > 0)
+        .map(|&(_, amount, _)| amount)
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+    
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut previous_level = 0;
+    for &level in levels.iter() {
+        let amount = contributions
+            .iter()
+            .map(|&(_, contributed, _)| contributed.min(level) - contributed.min(previous_level))
+            .sum();
+        let eligible_seats = contributions
+            .iter()
+            .filter(|&&(_, contributed, in_hand)| in_hand && contributed >= level)
+            .map(|&(seat, _, _)| seat)
+            .collect();
+        pots.push(Pot { amount, eligible_seats });
+        previous_level = level;
+    }
+    
+    // Chips a folded player put in above the highest live contribution are dead money
+    let dead_money: u64 = contributions
+        .iter()
+        .map(|&(_, contributed, _)| contributed.saturating_sub(previous_level))
+        .sum();
+    if let Some(last_pot) = pots.last_mut() {
+        last_pot.amount = last_pot.amount.checked_add(dead_money).unwrap();
+    }
+    
+    pots
+}
+
+/// Helper function to divide a hi-lo pot between its high and low winners
+///
+/// The high half keeps the odd chip, and the high hand scoops when no low qualifies.
+fn split_hi_lo(
+    amount: u64,
+    high_winners: &[usize],
+    low_winners: &[usize],
+    dealer_index: u8,
+    max_players: u8,
+) -> Vec<(usize, u64)> {
+    let low_half = if low_winners.is_empty() { 0 } else { amount / 2 };
+    let high_half = amount.checked_sub(low_half).unwrap();
+    
+    let mut shares = split_pot(high_half, high_winners, dealer_index, max_players);
+    shares.extend(split_pot(low_half, low_winners, dealer_index, max_players));
+    shares
+}
+
+/// Helper function to find the chips the biggest contributor put in that nobody matched
+///
+/// Takes the same entries as `build_pots` and returns the seat and the uncalled amount.
+fn uncalled_excess(contributions: &[(usize, u64, bool)]) -> Option<(usize, u64)> {
+    let (top_index, &(seat, top, _)) = contributions
+        .iter()
+        .enumerate()
+        .max_by_key(|&(_, &(_, contributed, _))| contributed)?;
+    let called = contributions
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != top_index)
+        .map(|(_, &(_, contributed, _))| contributed)
+        .max()
+        .unwrap_or(0);
+    
+    if top > called {
+        Some((seat, top - called))
+    } else {
+        None
+    }
+}
+
+/// Helper function to divide a pot between tied winners
+///
+/// Odd chips go one at a time to the winners closest to the left of the dealer.
+fn split_pot(amount: u64, winners: &[usize], dealer_index: u8, max_players: u8) -> Vec<(usize, u64)> {
+    if winners.is_empty() {
+        return Vec::new();
+    }
+    
+    let mut ordered = winners.to_vec();
+    let seats = max_players as usize;
+    ordered.sort_by_key(|&seat| (seat + seats - dealer_index as usize - 1) % seats);
+    
+    let share = amount / ordered.len() as u64;
+    let remainder = (amount % ordered.len() as u64) as usize;
+    ordered
+        .into_iter()
+        .enumerate()
+        .map(|(i, seat)| (seat, if i < remainder { share + 1 } else { share }))
+        .collect()
+}
+
+/// Helper function to get how many community cards must be public during a round
+fn board_cards_for_round(round: &Round) -> u8 {
+    match round {
+        Round::Flop => 3,
+        Round::Turn => 4,
+        Round::River | Round::Showdown => 5,
+        _ => 0,
+    }
+}
+
+/// Helper function to get how many upcards each live stud player shows during a round
+fn upcards_for_round(round: &Round) -> u8 {
+    match round {
+        Round::ThirdStreet => 1,
+        Round::FourthStreet => 2,
+        Round::FifthStreet => 3,
+        Round::SixthStreet | Round::SeventhStreet => 4,
+        _ => 0,
+    }
+}
+
+/// Helper function to check that the cards opening the current street are public
+///
+/// Only the board matters for flop games; in stud every live player's upcards must be shown.
+fn street_revealed(table: &Table, player_states: &[PlayerState]) -> bool {
+    let upcards = upcards_for_round(&table.round);
+    let upcards_shown = table.players.iter().enumerate().all(|(i, player_pubkey)| {
+        let player_state = &player_states[i];
+        *player_pubkey == Pubkey::default()
+            || !player_state.is_active
+            || player_state.is_folded
+            || player_state.upcards_revealed >= upcards
+    });
+    table.community_revealed >= board_cards_for_round(&table.round) && upcards_shown
+}
+
+/// Helper function to check whether the hand is waiting on the dealer to reveal cards
+///
+/// At showdown the whole board must be out, including streets run out with nobody left to bet.
+fn dealer_reveal_pending(table: &Table, player_states: &[PlayerState]) -> bool {
+    if table.round == Round::Showdown {
+        (table.community_revealed as usize) < board_card_count(&table.game_variant)
+    } else {
+        !street_revealed(table, player_states)
+    }
+}
+
+/// Helper function to return every seated player's contribution to the current hand
+fn refund_contributions(table: &Table, player_states: &mut [PlayerState]) {
+    for (i, player_pubkey) in table.players.iter().enumerate() {
+        if *player_pubkey == Pubkey::default() {
+            continue;
+        }
+        
+        let player_state = &mut player_states[i];
+        player_state.chips = player_state.chips.checked_add(player_state.total_contributed).unwrap();
+    }
+}
+
+/// Helper function to check whether any card appears more than once
+fn has_repeated_card(cards: &[u8]) -> bool {
+    (1..cards.len()).any(|i| cards[..i].contains(&cards[i]))
+}
+
+/// Helper function to check a card against the dealer's Merkle commitment to the deck
+///
+/// Leaves are `hash("card" || position || card || salt)` for positions 0..64; positions past
+/// the end of the deck are padding and never dealt. A proven card outside the variant's deck
+/// is the dealer's fault and is treated as a misdeal by the callers.
+fn verify_deck_card(
+    deck_commitment: &[u8; 32],
+    position: u8,
+    card: u8,
+    salt: &[u8; 32],
+    proof: &[[u8; 32]; DECK_TREE_DEPTH],
+) -> bool {
+    let mut node = hashv(&[b"card", &[position], &[card], salt]).to_bytes();
+    let mut index = position as usize;
+    for sibling in proof.iter() {
+        node = if index & 1 == 0 {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        };
+        index /= 2;
+    }
+    
+    node == *deck_commitment
+}
+
+/// Generate a shuffled deck of `deck_size` cards (simplified for this example)
+///
+/// Entries are positions in the dealer's committed deck, which holds `deck_cards` of the
+/// table's variant, e.g. 36 cards from sixes to aces in short deck.
+fn generate_shuffled_deck(seed: u64, deck_size: usize) -> Vec<u8> {
+    let mut deck: Vec<u8> = (0..deck_size as u8).collect();
+    
+    // Simple Fisher-Yates shuffle based on seed
+    let mut rng = seed;
+    for i in (1..deck_size).rev() {
+        // Simple LCG random number generator
+        rng = (rng.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407)) % u64::MAX;
+        let j = (rng % (i as u64 + 1)) as usize;
+        deck.swap(i, j);
+    }
+    
+    deck
+}
+
+/// Helper function to get the number of hole cards dealt in a game variant
+fn hole_card_count(variant: &GameVariant) -> usize {
+    match variant {
+        GameVariant::Holdem | GameVariant::ShortDeck => 2,
+        GameVariant::Omaha | GameVariant::OmahaHiLo => 4,
+        GameVariant::FiveCardDraw => 5,
+        GameVariant::SevenCardStud => 7,
+    }
+}
+
+/// Helper function to get the number of community cards dealt in a game variant
+fn board_card_count(variant: &GameVariant) -> usize {
+    match variant {
+        GameVariant::FiveCardDraw | GameVariant::SevenCardStud => 0,
+        _ => 5,
+    }
+}
+
+/// Helper function to get the most seats a variant's deck can deal to
+fn max_seats(variant: &GameVariant) -> u8 {
+    match variant {
+        GameVariant::FiveCardDraw => MAX_DRAW_PLAYERS,
+        GameVariant::SevenCardStud => MAX_STUD_PLAYERS,
+        _ => 9,
+    }
+}
+
+/// Helper function to list the cards making up a variant's deck
+///
+/// Short deck removes the deuces through fives, leaving 36 cards from sixes to aces.
+fn deck_cards(variant: &GameVariant) -> Vec<u8> {
+    match variant {
+        GameVariant::ShortDeck => (0..52).filter(|&card| card_rank(card) >= 4).collect(),
+        _ => (0..52).collect(),
+    }
+}
+
+/// Helper function to evaluate a player's hand under the rules of the table's variant
+///
+/// Hold'em and stud play the best five of all seven cards and draw plays the five-card hand
+/// as is; Omaha must use exactly two hole cards and three board cards.
+fn evaluate_variant_hand(variant: &GameVariant, hole_cards: &[u8], board: &[u8]) -> u32 {
+    match variant {
+        GameVariant::Holdem
+        | GameVariant::ShortDeck
+        | GameVariant::FiveCardDraw
+        | GameVariant::SevenCardStud => {
+            let mut cards = Vec::with_capacity(hole_cards.len() + board.len());
+            cards.extend_from_slice(hole_cards);
+            cards.extend_from_slice(board);
+            evaluate_poker_hand(&cards, *variant == GameVariant::ShortDeck)
+        }
+        GameVariant::Omaha | GameVariant::OmahaHiLo => {
+            let mut best = 0;
+            for_each_omaha_hand(hole_cards, board, |cards| {
+                best = std::cmp::max(best, evaluate_poker_hand(&cards, false));
+            });
+            best
+        }
+    }
+}
+
+/// Helper function to evaluate the best eight-or-better low in Omaha Hi-Lo
+///
+/// Uses exactly two hole cards and three board cards, aces play low, and straights and
+/// flushes are ignored. Returns `None` when no low qualifies; otherwise a lower value is a
+/// better low, compared from the highest card down.
+fn evaluate_low_hand(hole_cards: &[u8], board: &[u8]) -> Option<u32> {
+    let mut best: Option<u32> = None;
+    for_each_omaha_hand(hole_cards, board, |cards| {
+        let mut ranks = cards.map(|card| low_rank(card) as u32);
+        ranks.sort_unstable_by(|a, b| b.cmp(a));
+        
+        // Five distinct ranks of eight or lower
+        if ranks[0] > 8 || ranks.windows(2).any(|pair| pair[0] == pair[1]) {
+            return;
+        }
+        
+        let value = ranks.iter().fold(0, |value, &rank| (value << 4) | rank);
+        best = Some(best.map_or(value, |current| std::cmp::min(current, value)));
+    });
+    best
+}
+
+/// Helper function to get a card's rank when aces play low (ace = 1, deuce = 2, ..., king = 13)
+fn low_rank(card: u8) -> u8 {
+    let rank = card_rank(card);
+    if rank == 12 {
+        1
+    } else {
+        rank + 2
+    }
+}
+
+/// Helper function to visit every Omaha hand of exactly two hole cards and three board cards
+///
+/// Hands are picked by index into a fixed array, so walking all 60 of them allocates nothing.
+fn for_each_omaha_hand(hole_cards: &[u8], board: &[u8], mut visit: impl FnMut([u8; 5])) {
+    for first in 0..hole_cards.len() {
+        for second in first + 1..hole_cards.len() {
+            for third in 0..board.len() {
+                for fourth in third + 1..board.len() {
+                    for fifth in fourth + 1..board.len() {
+                        visit([hole_cards[first], hole_cards[second], board[third], board[fourth], board[fifth]]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Rank of a card from the 0..52 deck encoding (0 = deuce, 12 = ace)
+fn card_rank(card: u8) -> u8 {
+    card % 13
+}
+
+/// Suit of a card from the 0..52 deck encoding
+fn card_suit(card: u8) -> u8 {
+    card / 13
+}
+
+/// Evaluate the best five-card hand that can be made from five to seven cards.
+///
+/// The returned value is totally ordered: a higher value beats a lower one and equal
+/// values split the pot. The hand category sits above `HAND_CATEGORY_SHIFT` and the five
+/// deciding ranks (kickers included) are packed below it, most significant first.
+/// With `short_deck` set, A-6-7-8-9 is a straight and a flush outranks a full house.
+/// Fewer cards, such as stud upcards, are ranked by their pairs and high cards alone.
+fn evaluate_poker_hand(cards: &[u8], short_deck: bool) -> u32 {
+    let mut rank_counts = [0u8; 13];
+    let mut suit_masks = [0u16; 4];
+    let mut rank_mask: u16 = 0;
+    for &card in cards.iter() {
+        let rank = card_rank(card);
+        rank_counts[rank as usize] += 1;
+        suit_masks[card_suit(card) as usize] |= 1 << rank;
+        rank_mask |= 1 << rank;
+    }
+
+    // At most one suit can hold five or more cards out of seven
+    let flush_mask = suit_masks.iter().copied().find(|mask| mask.count_ones() >= 5);
+
+    // In short deck the ace also plays below the six, making A-6-7-8-9 a straight
+    let straight_ranks = |mask: u16| {
+        if short_deck {
+            mask | (((mask >> 12) & 1) << 3)
+        } else {
+            mask
+        }
+    };
+
+    if let Some(mask) = flush_mask {
+        if let Some(high) = straight_high_rank(straight_ranks(mask)) {
+            return pack_hand_value(HandCategory::StraightFlush, &[high], short_deck);
+        }
+    }
+
+    // Masks of the ranks held four, three and two times
+    let mut quads: u16 = 0;
+    let mut trips: u16 = 0;
+    let mut pairs: u16 = 0;
+    for (rank, &count) in rank_counts.iter().enumerate() {
+        match count {
+            0 | 1 => {}
+            2 => pairs |= 1 << rank,
+            3 => trips |= 1 << rank,
+            _ => quads |= 1 << rank,
+        }
+    }
+
+    if quads != 0 {
+        let quad = highest_rank(quads);
+        let ranks = with_kickers(&[quad], rank_mask & !(1 << quad), 1);
+        return pack_hand_value(HandCategory::FourOfAKind, &ranks, short_deck);
+    }
+
+    // Short deck ranks a flush above a full house
+    if short_deck {
+        if let Some(mask) = flush_mask {
+            return pack_hand_value(HandCategory::Flush, &with_kickers(&[], mask, 5), short_deck);
+        }
+    }
+
+    if trips != 0 {
+        // A second set of trips plays as the pair of a full house
+        let trip = highest_rank(trips);
+        let pair_ranks = (trips & !(1 << trip)) | pairs;
+        if pair_ranks != 0 {
+            return pack_hand_value(HandCategory::FullHouse, &[trip, highest_rank(pair_ranks)], short_deck);
+        }
+    }
+
+    if let Some(mask) = flush_mask {
+        return pack_hand_value(HandCategory::Flush, &with_kickers(&[], mask, 5), short_deck);
+    }
+
+    if let Some(high) = straight_high_rank(straight_ranks(rank_mask)) {
+        return pack_hand_value(HandCategory::Straight, &[high], short_deck);
+    }
+
+    if trips != 0 {
+        let trip = highest_rank(trips);
+        let ranks = with_kickers(&[trip], rank_mask & !(1 << trip), 2);
+        return pack_hand_value(HandCategory::ThreeOfAKind, &ranks, short_deck);
+    }
+
+    if pairs.count_ones() >= 2 {
+        let high_pair = highest_rank(pairs);
+        let low_pair = highest_rank(pairs & !(1 << high_pair));
+        let ranks = with_kickers(&[high_pair, low_pair], rank_mask & !(1 << high_pair) & !(1 << low_pair), 1);
+        return pack_hand_value(HandCategory::TwoPair, &ranks, short_deck);
+    }
+
+    if pairs != 0 {
+        let pair = highest_rank(pairs);
+        let ranks = with_kickers(&[pair], rank_mask & !(1 << pair), 3);
+        return pack_hand_value(HandCategory::OnePair, &ranks, short_deck);
+    }
+
+    pack_hand_value(HandCategory::HighCard, &with_kickers(&[], rank_mask, 5), short_deck)
+}
+
+/// Helper function to find the highest straight in a rank bitmask, counting A-2-3-4-5
+fn straight_high_rank(rank_mask: u16) -> Option<u8> {
+    // Shift ranks up by one and copy the ace into the bottom bit so the wheel is contiguous
+    let extended = (rank_mask << 1) | ((rank_mask >> 12) & 1);
+    for top in (4..=13u8).rev() {
+        let window = 0b11111u16 << (top - 4);
+        if extended & window == window {
+            return Some(top - 1);
+        }
+    }
+    None
+}
+
+/// Helper function to get the highest rank in a non-empty rank bitmask
+fn highest_rank(rank_mask: u16) -> u8 {
+    (15 - rank_mask.leading_zeros()) as u8
+}
+
+/// Helper function to follow a hand's leading ranks with the `count` highest ranks from a
+/// rank bitmask
+///
+/// Unused trailing slots stay zero, which packs the same as leaving them out.
+fn with_kickers(leading: &[u8], rank_mask: u16, count: usize) -> [u8; 5] {
+    let mut ranks = [0u8; 5];
+    ranks[..leading.len()].copy_from_slice(leading);
+    let kickers = (0..13u8).rev().filter(|rank| rank_mask & (1 << rank) != 0).take(count);
+    for (slot, rank) in ranks[leading.len()..].iter_mut().zip(kickers) {
+        *slot = rank;
+    }
+    ranks
+}
+
+/// Helper function to pack a hand category and its deciding ranks into a comparable value
+fn pack_hand_value(category: HandCategory, ranks: &[u8], short_deck: bool) -> u32 {
+    let category_value = match category {
+        HandCategory::Flush if short_deck => HandCategory::FullHouse as u32,
+        HandCategory::FullHouse if short_deck => HandCategory::Flush as u32,
+        _ => category as u32,
+    };
+    let mut value = category_value << HAND_CATEGORY_SHIFT;
+    for (i, &rank) in ranks.iter().take(5).enumerate() {
+        value |= (rank as u32) << (4 * (4 - i));
+    }
+    value
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GameAuthority::SIZE,
+        seeds = [b"game_authority"],
+        bump
+    )]
+    pub game_authority: Account<'info, GameAuthority>,
+    
+    pub fee_mint: Account<'info, token::Mint>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateGameAuthority<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Still in the first release's layout, so it is checked and parsed by hand
+    #[account(mut, seeds = [b"game_authority"], bump)]
+    pub game_authority: UncheckedAccount<'info>,
+    
+    pub fee_mint: Account<'info, token::Mint>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(mut, seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub new_authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"game_authority"],
+        bump = game_authority.bump,
+        constraint = game_authority.pending_authority == new_authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub game_authority: Account<'info, GameAuthority>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(mut, seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+    
+    #[account(
+        mut,
+        constraint = treasury.owner == game_authority.key() @ ErrorCode::InvalidTreasury,
+        constraint = treasury.mint == game_authority.fee_mint @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(table_id: String)]
+pub struct CreateTable<'info> {
+    #[account(mut)]
+    pub host: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + Table::SIZE,
+        seeds = [b"table", table_id.as_bytes()],
+        bump
+    )]
+    pub table: Account<'info, Table>,
+    
+    #[account(
+        init,
+        payer = host,
+        space = 8 + PlayerState::SIZE,
+        seeds = [b"player_state", host.key().as_ref(), table.key().as_ref()],
+        bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    
+    #[account(mut)]
+    pub host_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = host,
+        token::mint = mint,
+        token::authority = table,
+    )]
+    pub table_vault: Account<'info, TokenAccount>,
+    
+    pub mint: Account<'info, token::Mint>,
+    
+    #[account(mut, seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct JoinTable<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    #[account(
+        init,
+        payer = player,
+        space = 8 + PlayerState::SIZE,
+        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
+        bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    
+    #[account(mut)]
+    pub player_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut, address = table.vault @ ErrorCode::InvalidVault)]
+    pub table_vault: Account<'info, TokenAccount>,
+    
+    #[account(seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct StartGame<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    #[account(seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+}
+
+#[derive(Accounts)]
+pub struct DealerAction<'info> {
+    pub dealer: Signer<'info>,
+    
+    #[account(mut, has_one = dealer)]
+    pub table: Account<'info, Table>,
+    
+    #[account(seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+}
+
+#[derive(Accounts)]
+pub struct RevealStreet<'info> {
+    pub dealer: Signer<'info>,
+    
+    #[account(mut, has_one = dealer)]
+    pub table: Account<'info, Table>,
+}
+
+#[derive(Accounts)]
+pub struct DeliverHoleCards<'info> {
+    pub dealer: Signer<'info>,
+    
+    #[account(has_one = dealer)]
+    pub table: Account<'info, Table>,
+    
+    #[account(mut, constraint = player_state.table == table.key() @ ErrorCode::PlayerNotAtTable)]
+    pub player_state: Account<'info, PlayerState>,
+}
+
+#[derive(Accounts)]
+pub struct RevealHoleCards<'info> {
+    pub player: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
+        bump = player_state.bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+}
+
+#[derive(Accounts)]
+pub struct ShuffleAction<'info> {
+    pub player: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
+        bump = player_state.bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+}
+
+#[derive(Accounts)]
+pub struct CommitShuffle<'info> {
+    pub player: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
+        bump = player_state.bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    
+    /// CHECK: Address is constrained to the SlotHashes sysvar
+    #[account(address = slot_hashes::ID)]
+    pub recent_slothashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ForfeitUncommitted<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    /// CHECK: Address is constrained to the SlotHashes sysvar
+    #[account(address = slot_hashes::ID)]
+    pub recent_slothashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ForfeitUnrevealed<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+}
+
+#[derive(Accounts)]
+pub struct RequestRandomness<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    /// CHECK: Must be the oracle program configured on the table
+    #[account(address = table.vrf_oracle)]
+    pub oracle_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Owned and validated by the oracle program
+    #[account(mut)]
+    pub oracle_state: UncheckedAccount<'info>,
+    
+    #[account(seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+}
+
+#[derive(Accounts)]
+pub struct FulfillRandomness<'info> {
+    /// The oracle program's authority PDA, checked against the table's oracle in the instruction
+    pub oracle_authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    /// CHECK: Owned by the table's oracle program; parsed by `vrf_oracle::read_result`
+    #[account(owner = table.vrf_oracle @ ErrorCode::InvalidOracleResult)]
+    pub oracle_result: UncheckedAccount<'info>,
+    
+    #[account(seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRandomness<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerAction<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+}
+
+#[derive(Accounts)]
+pub struct Showdown<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    #[account(mut, seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+}
+
+#[derive(Accounts)]
+pub struct VoidHand<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+}
+
+#[derive(Accounts)]
+pub struct ResetTable<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+}
+
+#[derive(Accounts)]
+pub struct CollectRake<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    #[account(mut, seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+    
+    #[account(
+        mut,
+        constraint = treasury.owner == game_authority.key() @ ErrorCode::InvalidTreasury,
+        constraint = treasury.mint == game_authority.fee_mint @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    
+    #[account(mut, constraint = host_token_account.owner == table.host @ ErrorCode::NotTableHost)]
+    pub host_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = table.vault @ ErrorCode::InvalidVault,
+        constraint = table_vault.mint == treasury.mint @ ErrorCode::InvalidTreasury
+    )]
+    pub table_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct LeaveTable<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    #[account(
+        mut,
+        close = player,
+        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
+        bump = player_state.bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    
+    #[account(mut)]
+    pub player_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut, address = table.vault @ ErrorCode::InvalidVault)]
+    pub table_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct TimeoutAction<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    // Multi-table tournament tables have no vault and pay no reward from a stack, so these are
+    // only needed at cash tables
+    #[account(mut)]
+    pub cranker_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(mut, address = table.vault @ ErrorCode::InvalidVault)]
+    pub table_vault: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FundCrankBounty<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SitOut<'info> {
+    pub player: Signer<'info>,
+    
+    pub table: Account<'info, Table>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
+        bump = player_state.bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPrize<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    pub table: Account<'info, Table>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
+        bump = player_state.bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    
+    #[account(mut)]
+    pub player_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut, address = table.vault @ ErrorCode::InvalidVault)]
+    pub table_vault: Account<'info, TokenAccount>,
     
-    deck
+    pub token_program: Program<'info, Token>,
 }
 
-/// Simplified poker hand evaluation (returns a numeric value representing hand strength)
-fn evaluate_poker_hand(cards: &[u8]) -> u32 {
-    // In a real implementation, this would be a proper poker hand evaluator
-    // For simplicity, we're just returning a placeholder value
-    42
+#[derive(Accounts)]
+pub struct Rebuy<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    #[account(mut)]
+    pub table: Account<'info, Table>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
+        bump = player_state.bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    
+    #[account(mut)]
+    pub player_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut, address = table.vault @ ErrorCode::InvalidVault)]
+    pub table_vault: Account<'info, TokenAccount>,
+    
+    #[account(seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+    
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct Initialize<'info> {
+pub struct TournamentRebuy<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub player: Signer<'info>,
+    
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+    
+    #[account(constraint = table.tournament == tournament.key() @ ErrorCode::TableNotInTournament)]
+    pub table: Account<'info, Table>,
     
     #[account(
-        init,
-        payer = authority,
-        space = 8 + GameAuthority::SIZE,
-        seeds = [b"game_authority"],
-        bump
+        mut,
+        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
+        bump = player_state.bump
     )]
+    pub player_state: Account<'info, PlayerState>,
+    
+    #[account(mut)]
+    pub player_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut, address = tournament.vault @ ErrorCode::InvalidVault)]
+    pub tournament_vault: Account<'info, TokenAccount>,
+    
+    #[account(seeds = [b"game_authority"], bump = game_authority.bump)]
     pub game_authority: Account<'info, GameAuthority>,
     
-    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct CreateTable<'info> {
+#[instruction(tournament_id: String)]
+pub struct CreateTournament<'info> {
     #[account(mut)]
     pub host: Signer<'info>,
     
     #[account(
         init,
         payer = host,
-        space = 8 + Table::SIZE,
-        seeds = [b"table", table_id.as_bytes()],
+        space = 8 + Tournament::SIZE,
+        seeds = [b"tournament", tournament_id.as_bytes()],
         bump
     )]
-    pub table: Account<'info, Table>,
+    pub tournament: Account<'info, Tournament>,
     
     #[account(
         init,
         payer = host,
-        space = 8 + PlayerState::SIZE,
-        seeds = [b"player_state", host.key().as_ref(), table.key().as_ref()],
-        bump
+        token::mint = mint,
+        token::authority = tournament,
     )]
-    pub player_state: Account<'info, PlayerState>,
+    pub tournament_vault: Account<'info, TokenAccount>,
     
+    pub mint: Account<'info, token::Mint>,
+    
+    #[account(seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(table_id: String)]
+pub struct AddTournamentTable<'info> {
     #[account(mut)]
-    pub host_token_account: Account<'info, TokenAccount>,
+    pub host: Signer<'info>,
+    
+    #[account(mut, has_one = host)]
+    pub tournament: Account<'info, Tournament>,
     
     #[account(
         init,
         payer = host,
-        token::mint = mint,
-        token::authority = table,
+        space = 8 + Table::SIZE,
+        seeds = [b"table", table_id.as_bytes()],
+        bump
     )]
-    pub table_vault: Account<'info, TokenAccount>,
-    
-    pub mint: Account<'info, token::Mint>,
+    pub table: Account<'info, Table>,
     
-    #[account(mut)]
+    #[account(seeds = [b"game_authority"], bump = game_authority.bump)]
     pub game_authority: Account<'info, GameAuthority>,
     
-    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct JoinTable<'info> {
+pub struct RegisterTournament<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
     
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+    
     #[account(mut)]
     pub table: Account<'info, Table>,
     
@@ -721,96 +4663,142 @@ pub struct JoinTable<'info> {
     )]
     pub player_state: Account<'info, PlayerState>,
     
+    #[account(
+        init,
+        payer = player,
+        space = 8 + Entry::SIZE,
+        seeds = [b"entry", tournament.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, Entry>,
+    
     #[account(mut)]
     pub player_token_account: Account<'info, TokenAccount>,
     
-    #[account(mut)]
-    pub table_vault: Account<'info, TokenAccount>,
+    #[account(mut, address = tournament.vault @ ErrorCode::InvalidVault)]
+    pub tournament_vault: Account<'info, TokenAccount>,
+    
+    #[account(seeds = [b"game_authority"], bump = game_authority.bump)]
+    pub game_authority: Account<'info, GameAuthority>,
     
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct StartGame<'info> {
-    #[account(mut)]
+pub struct StartTournament<'info> {
     pub host: Signer<'info>,
     
     #[account(mut, has_one = host)]
-    pub table: Account<'info, Table>,
+    pub tournament: Account<'info, Tournament>,
+}
+
+#[derive(Accounts)]
+pub struct SyncTournamentTable<'info> {
+    pub tournament: Account<'info, Tournament>,
     
-    /// CHECK: We're checking all player states in the instruction
     #[account(mut)]
-    pub player_states: UncheckedAccount<'info>,
+    pub table: Account<'info, Table>,
 }
 
 #[derive(Accounts)]
-pub struct PlayerAction<'info> {
+pub struct MovePlayer<'info> {
     #[account(mut)]
-    pub player: Signer<'info>,
+    pub payer: Signer<'info>,
     
     #[account(mut)]
-    pub table: Account<'info, Table>,
+    pub tournament: Account<'info, Tournament>,
+    
+    #[account(mut)]
+    pub from_table: Account<'info, Table>,
+    
+    #[account(mut)]
+    pub to_table: Account<'info, Table>,
+    
+    /// CHECK: The player being moved; only used to derive their accounts
+    pub player: UncheckedAccount<'info>,
     
     #[account(
         mut,
-        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
-        bump = player_state.bump
+        close = payer,
+        seeds = [b"player_state", player.key().as_ref(), from_table.key().as_ref()],
+        bump = from_player_state.bump
     )]
-    pub player_state: Account<'info, PlayerState>,
+    pub from_player_state: Account<'info, PlayerState>,
     
-    /// CHECK: We're checking all player states in the instruction
-    #[account(mut)]
-    pub player_states: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PlayerState::SIZE,
+        seeds = [b"player_state", player.key().as_ref(), to_table.key().as_ref()],
+        bump
+    )]
+    pub to_player_state: Account<'info, PlayerState>,
+    
+    #[account(
+        mut,
+        seeds = [b"entry", tournament.key().as_ref(), player.key().as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Account<'info, Entry>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Showdown<'info> {
+pub struct BreakTable<'info> {
     #[account(mut)]
-    pub host: Signer<'info>,
+    pub tournament: Account<'info, Tournament>,
     
-    #[account(mut, has_one = host)]
-    pub table: Account<'info, Table>,
-    
-    /// CHECK: We're checking all player states in the instruction
     #[account(mut)]
-    pub player_states: UncheckedAccount<'info>,
+    pub table: Account<'info, Table>,
 }
 
 #[derive(Accounts)]
-pub struct ResetTable<'info> {
+pub struct EliminatePlayer<'info> {
     #[account(mut)]
-    pub host: Signer<'info>,
+    pub tournament: Account<'info, Tournament>,
     
-    #[account(mut, has_one = host)]
+    #[account(mut)]
     pub table: Account<'info, Table>,
     
-    /// CHECK: We're checking all player states in the instruction
-    #[account(mut)]
-    pub player_states: UncheckedAccount<'info>,
+    /// CHECK: The busted player; only used to derive their accounts
+    pub player: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
+        bump = player_state.bump
+    )]
+    pub player_state: Account<'info, PlayerState>,
+    
+    #[account(
+        mut,
+        seeds = [b"entry", tournament.key().as_ref(), player.key().as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Account<'info, Entry>,
 }
 
 #[derive(Accounts)]
-pub struct LeaveTable<'info> {
+pub struct ClaimTournamentPrize<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
     
-    #[account(mut)]
-    pub table: Account<'info, Table>,
+    pub tournament: Account<'info, Tournament>,
     
     #[account(
         mut,
-        seeds = [b"player_state", player.key().as_ref(), table.key().as_ref()],
-        bump = player_state.bump
+        seeds = [b"entry", tournament.key().as_ref(), player.key().as_ref()],
+        bump = entry.bump
     )]
-    pub player_state: Account<'info, PlayerState>,
+    pub entry: Account<'info, Entry>,
     
     #[account(mut)]
     pub player_token_account: Account<'info, TokenAccount>,
     
-    #[account(mut)]
-    pub table_vault: Account<'info, TokenAccount>,
+    #[account(mut, address = tournament.vault @ ErrorCode::InvalidVault)]
+    pub tournament_vault: Account<'info, TokenAccount>,
     
     pub token_program: Program<'info, Token>,
 }
@@ -821,20 +4809,39 @@ pub struct GameAuthority {
     pub fee_percentage: u8,
     pub total_games_played: u64,
     pub total_fees_collected: u64,
+    pub rake_cap_big_blinds: u8, // Most a cash hand is raked, in big blinds (0 = uncapped)
+    pub protocol_fee_bps: u16,   // Share of the rake kept by the protocol
+    pub host_fee_bps: u16,       // Share of the rake paid to the table host
+    pub referral_fee_bps: u16,   // Share of the rake set aside for the referral pool
+    pub protocol_fees: u64,      // Protocol share held in the treasury, not yet withdrawn
+    pub referral_fees: u64,      // Referral pool held in the treasury, not yet withdrawn
+    pub pending_authority: Pubkey, // Proposed new authority awaiting acceptance (default if none)
+    pub signers: Vec<Pubkey>,    // Optional multisig signer set; empty when the authority acts alone
+    pub threshold: u8,           // Signers from `signers` needed to approve an authority action
+    pub paused: bool,            // Emergency stop for the actions selected by `pause_scope`
+    pub pause_scope: u8,         // `PAUSE_*` flags for the actions refused while paused
+    pub oracles: Vec<Pubkey>,    // VRF oracle programs tables are allowed to use
+    pub dealers: Vec<Pubkey>,    // Off-chain dealers tables are allowed to use
+    pub fee_mint: Pubkey,        // Mint every fee is collected and held in
     pub bump: u8,
 }
 
 impl GameAuthority {
-    pub const SIZE: usize = 32 + 1 + 8 + 8 + 1;
+    pub const SIZE: usize = 32 + 1 + 8 + 8 + 1 + 2 + 2 + 2 + 8 + 8 + 32
+        + (4 + MAX_AUTHORITY_SIGNERS * 32) + 1 + 1 + 1 + (4 + MAX_ORACLES * 32)
+        + (4 + MAX_DEALERS * 32) + 32 + 1;
 }
 
 #[account]
 pub struct Table {
     pub host: Pubkey,
     pub table_id: String,
-    pub buy_in: u64,
+    pub buy_in: u64,                // Sit-and-go entry fee, or the host's buy-in at a cash table
+    pub min_buy_in: u64,
+    pub max_buy_in: u64,
     pub small_blind: u64,
     pub big_blind: u64,
+    pub ante: u64,                  // Posted by every player each hand (0 for no ante)
     pub max_players: u8,
     pub is_private: bool,
     pub status: TableStatus,
@@ -845,36 +4852,341 @@ pub struct Table {
     pub dealer_index: u8,
     pub round: Round,
     pub highest_bet: u64,
+    pub last_raise_size: u64,       // Size of the last full raise on this street
+    pub betting_structure: BettingStructure,
+    pub game_variant: GameVariant,
+    pub bets_this_street: u8,       // Bet plus full raises so far, for the fixed-limit cap
     pub community_cards: [u8; 5],
+    pub commit_count: u8,           // Players who committed to a shuffle secret this hand
+    pub reveal_count: u8,           // Players who revealed their shuffle secret this hand
+    pub reveal_deadline: i64,       // Unix timestamp closing the reveal window (0 while committing)
+    pub commit_deadline: i64,       // Unix timestamp closing the commit window (0 before any commit)
+    pub shuffle_entropy: [u8; 32],  // Running hash of every revealed shuffle secret
+    pub vrf_oracle: Pubkey,         // Oracle program dealing this table (default = commit-reveal)
+    pub randomness_nonce: u64,      // Number of randomness requests made so far
+    pub pending_randomness: [u8; 32], // Id of the outstanding oracle request (zero if none)
+    pub randomness_requested_at: i64, // Unix timestamp of the outstanding oracle request
+    pub dealer: Pubkey,             // Off-chain dealer that knows the committed deck order
+    pub deck_commitment: [u8; 32],  // Merkle root over (position, card, salt) for this hand
+    pub community_positions: [u8; 5], // Committed deck positions of the board cards
+    pub community_revealed: u8,     // Number of community cards opened so far
+    pub showdown_deadline: i64,     // Unix timestamp after which unrevealed hands are mucked
+    pub finished_at: i64,           // Unix timestamp the last hand finished
+    pub dealer_deadline: i64,       // Unix timestamp after which a hand stalled on the dealer can be voided
+    pub misdeal: bool,              // Set when reveals prove the committed deck is invalid
+    pub deck_seed: u64,             // Seed that permuted the deck positions for this hand
+    pub deck_position: u8,          // Index of the next undealt card in the permuted deck
+    pub game_mode: GameMode,
+    pub hands_played: u64,          // Hands dealt at this table so far
+    pub starting_stack: u64,        // Chips each tournament entrant starts with
+    pub prize_pool: u64,            // Tournament buy-ins held for the payouts
+    pub blind_levels: Vec<BlindLevel>, // Tournament blind schedule, one entry per level
+    pub level_clock: LevelClock,
+    pub level_duration: u64,        // Length of each blind level in hands or seconds
+    pub blind_level: u8,            // Index of the current entry in `blind_levels`
+    pub level_started_hand: u64,    // Value of `hands_played` when the current level began
+    pub level_started_at: i64,      // Unix timestamp when the current level began
+    pub payouts_bps: Vec<u16>,      // Share of the prize pool for each place, first place first
+    pub tournament: Pubkey,         // Multi-table tournament this table belongs to (default if none)
+    pub max_stack: u64,             // Largest stack a cash top-up may reach (0 = the buy-in)
+    pub max_rebuys: u8,             // Tournament rebuys allowed per player
+    pub rebuy_levels: u8,           // Tournament blind levels rebuys are open for; the add-on follows
+    pub add_on_chips: u64,          // Chips the tournament add-on buys (0 = no add-on)
+    pub recent_departures: Vec<Departure>, // Cash players who left within `RATHOLE_WINDOW`
+    pub action_timeout: i64,        // Seconds each player has to act on their turn
+    pub sit_out_on_timeout: bool,   // Whether timed-out players sit out until they return
+    pub turn_started_at: i64,       // Unix timestamp when the player to act got their turn
+    pub crank_bounty: u64,          // Lamports held in this account to pay timeout cranks
+    pub time_bank: i64,             // Seconds of time bank each player starts with, and its cap
+    pub time_bank_refill: i64,      // Seconds added back to every time bank on a refill
+    pub time_bank_refill_hands: u64, // Hands between time bank refills (0 = never)
+    pub rake_percentage: u8,        // Share of each cash pot raked, fixed when the table is created
+    pub rake_cap_big_blinds: u8,    // Most a hand is raked, in big blinds (0 = uncapped)
+    pub rake: u64,                  // Rake taken this hand, counted against the cap
+    pub rake_due: u64,              // Protocol and referral rake held in the vault until `collect_rake` pays it out
+    pub host_fee_bps: u16,          // Share of the rake owed to the host, fixed when the table is created
+    pub host_rake_due: u64,         // Host's share of the rake taken while the current host hosted
+    pub vault: Pubkey,              // Token account holding the chips in play (default at multi-table tournaments)
     pub bump: u8,
 }
 
 impl Table {
-    pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 8 + (9 * 32) + 1 + 1 + 1 + 1 + 8 + (5 * 1) + 1;
+    pub const SIZE: usize = 32 + (4 + 32) + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 8 + (4 + 9 * 32)
+        + 1 + 1 + 1 + 1 + 8 + 8 + 1 + 1 + 1 + 5 + 1 + 1 + 8 + 8 + 32 + 32 + 8 + 32 + 32 + 32
+        + 5 + 1 + 8 + 1 + 8 + 1 + 1 + 8 + 8 + 8
+        + (4 + MAX_BLIND_LEVELS * BlindLevel::SIZE) + 1 + 8 + 1 + 8 + 8 + (4 + 9 * 2) + 32 + 8 + 1 + 1 + 8
+        + (4 + MAX_RECENT_DEPARTURES * Departure::SIZE) + 8 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 8 + 8 + 32 + 8 + 8 + 8 + 2 + 8 + 1;
+}
+
+#[account]
+#[derive(Default)]
+pub struct PlayerState {
+    pub player: Pubkey,
+    pub table: Pubkey,
+    pub chips: u64,
+    pub is_active: bool,
+    pub is_folded: bool,
+    pub is_all_in: bool,
+    pub has_acted: bool,              // Acted on this street since the last raise
+    pub last_action_bet: u64,         // Highest bet on the table when this player last acted
+    pub current_bet: u64,
+    pub total_contributed: u64, // Chips put into the pot across every round of the hand
+    pub cards: [u8; MAX_HOLE_CARDS],  // Only meaningful once `cards_revealed` is set
+    pub shuffle_commitment: [u8; 32], // hash(secret || player) submitted before the hand
+    pub has_revealed: bool,
+    pub is_sitting_out: bool,         // Takes no part in the current hand
+    pub encryption_key: [u8; 32],     // X25519 public key hole cards are encrypted to
+    pub hole_positions: [u8; MAX_HOLE_CARDS], // Committed deck positions of this player's hole cards
+    pub encrypted_cards: Vec<u8>,     // Hole cards and salts sealed to `encryption_key`
+    pub cards_revealed: bool,
+    pub upcards: [u8; STUD_UPCARDS],  // Stud cards dealt face up, in street order
+    pub upcards_revealed: u8,         // Number of `upcards` shown so far
+    pub finishing_place: u16,         // Tournament place once eliminated (0 while playing or for the winner)
+    pub prize_claimed: bool,
+    pub rebuys: u8,                   // Sit-and-go rebuys taken
+    pub add_on_taken: bool,
+    pub is_away: bool,                // Sits out every hand until the player returns
+    pub time_bank: i64,               // Seconds left to act beyond the table's action window
+    pub bump: u8,
+}
+
+impl PlayerState {
+    pub const SIZE: usize = 32 + 32 + 8 + 1 + 1 + 1 + 1 + 8 + 8 + 8 + MAX_HOLE_CARDS + 32 + 1 + 1
+        + 32 + MAX_HOLE_CARDS + (4 + MAX_ENCRYPTED_CARDS_LEN) + 1 + STUD_UPCARDS + 1 + 2 + 1 + 1 + 1 + 1 + 8 + 1;
+}
+
+#[account]
+pub struct Tournament {
+    pub host: Pubkey,
+    pub tournament_id: String,
+    pub buy_in: u64,
+    pub max_entrants: u16,
+    pub seats_per_table: u8,
+    pub dealer: Pubkey,               // Off-chain dealer for every table of the tournament
+    pub betting_structure: BettingStructure,
+    pub game_variant: GameVariant,
+    pub starting_stack: u64,
+    pub blind_levels: Vec<BlindLevel>,
+    pub level_duration: u64,          // Length of each blind level in seconds
+    pub payouts_bps: Vec<u16>,        // Share of the prize pool for each place, first place first
+    pub status: TournamentStatus,
+    pub entrant_count: u16,
+    pub players_remaining: u16,
+    pub prize_pool: u64,
+    pub started_at: i64,              // Unix timestamp the blind clock started from
+    pub tables: Vec<Pubkey>,          // Tables still in play
+    pub table_counts: Vec<u8>,        // Players seated at each of `tables`
+    pub final_table: Pubkey,          // Set once a single table remains
+    pub vault: Pubkey,                // Token account holding the prize pool
+    pub max_rebuys: u8,               // Rebuys allowed per player
+    pub rebuy_levels: u8,             // Blind levels rebuys are open for; the add-on follows
+    pub add_on_chips: u64,            // Chips the add-on buys (0 = no add-on)
+    pub bump: u8,
+}
+
+impl Tournament {
+    pub const SIZE: usize = 32 + (4 + 32) + 8 + 2 + 1 + 32 + 1 + 1 + 8 + (4 + MAX_BLIND_LEVELS * BlindLevel::SIZE) + 8
+        + (4 + MAX_TOURNAMENT_PAYOUTS * 2) + 1 + 2 + 2 + 8 + 8 + (4 + MAX_TOURNAMENT_TABLES * 32)
+        + (4 + MAX_TOURNAMENT_TABLES) + 32 + 32 + 1 + 1 + 8 + 1;
+}
+
+#[account]
+pub struct Entry {
+    pub tournament: Pubkey,
+    pub player: Pubkey,
+    pub table: Pubkey,                // Table the player is currently seated at
+    pub finishing_place: u16,         // Place once eliminated (0 while playing or for the winner)
+    pub prize_claimed: bool,
+    pub bump: u8,
+}
+
+impl Entry {
+    pub const SIZE: usize = 32 + 32 + 32 + 2 + 1 + 1;
+}
+
+/// A main or side pot built at showdown and the seats that can win it
+pub struct Pot {
+    pub amount: u64,
+    pub eligible_seats: Vec<usize>,
+}
+
+/// Forced bets for one level of a tournament blind schedule
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct BlindLevel {
+    pub small_blind: u64,
+    pub big_blind: u64,
+    pub ante: u64,
+}
+
+impl BlindLevel {
+    pub const SIZE: usize = 8 + 8 + 8;
+}
+
+/// Settings that turn a table into a sit-and-go tournament
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TournamentConfig {
+    pub starting_stack: u64,
+    pub blind_levels: Vec<BlindLevel>,
+    pub level_clock: LevelClock,
+    pub level_duration: u64,
+    pub payouts_bps: Vec<u16>,
+}
+
+/// Limits on adding chips to a stack after the initial buy-in
+///
+/// Cash tables use `max_stack` for top-ups; tournaments use the rebuy and add-on settings,
+/// each priced at the buy-in.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RebuyConfig {
+    pub max_stack: u64,
+    pub max_rebuys: u8,
+    pub rebuy_levels: u8,
+    pub add_on_chips: u64,
+}
+
+/// Per-table limits on how long a player may take to act
+///
+/// Each player also has a time bank of `time_bank` seconds that is spent whenever they go past
+/// `action_timeout`, and regains `time_bank_refill` seconds every `time_bank_refill_hands` hands.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ActionClockConfig {
+    pub action_timeout: i64,
+    pub sit_out_on_timeout: bool,
+    pub time_bank: i64,
+    pub time_bank_refill: i64,
+    pub time_bank_refill_hands: u64,
+}
+
+/// Game authority layout written by the first release, read once by `migrate_game_authority`
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyGameAuthority {
+    pub authority: Pubkey,
+    pub fee_percentage: u8,
+    pub total_games_played: u64,
+    pub total_fees_collected: u64,
+    pub bump: u8,
+}
+
+impl LegacyGameAuthority {
+    pub const SIZE: usize = 32 + 1 + 8 + 8 + 1;
+}
+
+/// A cash player who recently left a table and the stack they took with them
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Departure {
+    pub player: Pubkey,
+    pub chips: u64,
+    pub left_at: i64,
+}
+
+impl Departure {
+    pub const SIZE: usize = 32 + 8 + 8;
+}
+
+#[event]
+pub struct FeeUpdated {
+    pub old_fee_percentage: u8,
+    pub fee_percentage: u8,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub rake_cap_big_blinds: u8,
+    pub protocol_fee_bps: u16,
+    pub host_fee_bps: u16,
+    pub referral_fee_bps: u16,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct AuthoritySignersUpdated {
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct OraclesUpdated {
+    pub oracles: Vec<Pubkey>,
+}
+
+#[event]
+pub struct DealersUpdated {
+    pub dealers: Vec<Pubkey>,
+}
+
+#[event]
+pub struct DealerRemoved {
+    pub dealer: Pubkey,
+    pub table: Pubkey,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub paused: bool,
+    pub pause_scope: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum TableStatus {
+    Waiting,
+    Playing,
+    Finished,
+    Completed, // Tournament over; prizes can be claimed
+    Closed,    // Broken multi-table tournament table
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum GameMode {
+    CashGame,
+    SitAndGo,
+    MultiTable,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum FeeBucket {
+    Protocol,
+    Referral,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum TournamentStatus {
+    Registering,
+    Running,
+    Completed,
 }
 
-#[account]
-pub struct PlayerState {
-    pub player: Pubkey,
-    pub table: Pubkey,
-    pub chips: u64,
-    pub is_active: bool,
-    pub is_folded: bool,
-    pub is_all_in: bool,
-    pub current_bet: u64,
-    pub cards: [u8; 2],
-    pub bump: u8,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum LevelClock {
+    Hands,
+    Time,
 }
 
-impl PlayerState {
-    pub const SIZE: usize = 32 + 32 + 8 + 1 + 1 + 1 + 8 + (2 * 1) + 1;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum BettingStructure {
+    NoLimit,
+    PotLimit,
+    FixedLimit,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
-pub enum TableStatus {
-    Waiting,
-    Playing,
-    Finished,
+pub enum GameVariant {
+    Holdem,
+    Omaha,
+    OmahaHiLo,
+    ShortDeck,
+    FiveCardDraw,
+    SevenCardStud,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
@@ -884,9 +5196,133 @@ pub enum Round {
     Flop,
     Turn,
     River,
+    PreDraw,
+    Draw,
+    PostDraw,
+    ThirdStreet,
+    FourthStreet,
+    FifthStreet,
+    SixthStreet,
+    SeventhStreet,
     Showdown,
 }
 
+/// Seconds players have to reveal their shuffle secrets once everyone has committed
+pub const SHUFFLE_REVEAL_WINDOW: i64 = 120;
+
+/// Seconds players have to commit to a shuffle secret once the first player has committed
+pub const SHUFFLE_COMMIT_WINDOW: i64 = 120;
+
+/// Seconds live players have to open their hole cards once the hand reaches showdown
+pub const SHOWDOWN_REVEAL_WINDOW: i64 = 120;
+
+/// Seconds a tournament table waits for its host before anyone may move it on
+pub const HOST_GRACE_PERIOD: i64 = 60;
+
+/// Seconds an oracle has to fulfil a randomness request before anyone may cancel it
+pub const RANDOMNESS_REQUEST_TIMEOUT: i64 = 300;
+
+/// Seconds the dealer has to reveal the cards for a new street before the hand can be voided
+pub const DEALER_REVEAL_WINDOW: i64 = 120;
+
+/// Pause flag for creating tables and tournaments
+pub const PAUSE_CREATE: u8 = 1 << 0;
+
+/// Pause flag for joining tables, registering for tournaments and buying more chips
+pub const PAUSE_JOIN: u8 = 1 << 1;
+
+/// Pause flag for dealing new hands
+pub const PAUSE_START: u8 = 1 << 2;
+
+/// Every pause flag
+pub const PAUSE_ALL: u8 = PAUSE_CREATE | PAUSE_JOIN | PAUSE_START;
+
+/// Most VRF oracle programs the authority may approve
+pub const MAX_ORACLES: usize = 8;
+
+/// Most off-chain dealers the authority may approve
+pub const MAX_DEALERS: usize = 16;
+
+/// Most members an authority signer set may have
+pub const MAX_AUTHORITY_SIGNERS: usize = 10;
+
+/// Seconds players at tournament tables have to act on their turn
+pub const DEFAULT_ACTION_TIMEOUT: i64 = 30;
+
+/// Seconds of time bank players at tournament tables start with
+pub const DEFAULT_TIME_BANK: i64 = 60;
+
+/// Share of a betting unit, in basis points, paid to whoever times out a stalled player
+pub const TIMEOUT_CRANK_REWARD_BPS: u64 = 500;
+
+/// Lamports paid from a table's crank bounty for a timeout no stack pays for
+pub const TIMEOUT_CRANK_BOUNTY: u64 = 50_000;
+
+/// Lamports a new table's creator escrows as its crank bounty
+pub const CRANK_BOUNTY_ESCROW: u64 = 10_000_000;
+
+/// Depth of the Merkle tree committing the dealer's deck (64 leaves)
+pub const DECK_TREE_DEPTH: usize = 6;
+
+/// Most hole cards any supported variant deals to a player
+pub const MAX_HOLE_CARDS: usize = 7;
+
+/// Maximum size of a player's encrypted hole cards (nonce, cards with salts, and tag)
+pub const MAX_ENCRYPTED_CARDS_LEN: usize = 288;
+
+/// Most cards a player may replace in the draw
+pub const MAX_DRAW_CARDS: u32 = 3;
+
+/// Most players at a draw table, so five cards each plus full draws fit in one deck
+pub const MAX_DRAW_PLAYERS: u8 = 6;
+
+/// Face-down cards a stud player holds before their first upcard; the seventh card is also
+/// dealt down, after the upcards
+pub const STUD_DOWN_CARDS: usize = 2;
+
+/// Cards a stud player receives face up, one on each of third through sixth street
+pub const STUD_UPCARDS: usize = 4;
+
+/// Most players at a stud table, so seven cards each fit in one deck
+pub const MAX_STUD_PLAYERS: u8 = 7;
+
+/// Most levels a tournament blind schedule can hold
+pub const MAX_BLIND_LEVELS: usize = 16;
+
+/// Most tables a multi-table tournament can run
+pub const MAX_TOURNAMENT_TABLES: usize = 16;
+
+/// Most places a multi-table tournament can pay
+pub const MAX_TOURNAMENT_PAYOUTS: usize = 32;
+
+/// Seconds after leaving a cash table during which a player must rejoin with their old stack
+pub const RATHOLE_WINDOW: i64 = 2 * 60 * 60;
+
+/// Most recent departures a table remembers for anti-ratholing
+pub const MAX_RECENT_DEPARTURES: usize = 32;
+
+/// Basis points a tournament payout table must add up to
+pub const PAYOUT_BPS_TOTAL: u16 = 10_000;
+
+/// Bets allowed per street in fixed-limit games (a bet and three raises)
+pub const FIXED_LIMIT_RAISE_CAP: u8 = 4;
+
+/// Bit offset of the hand category within a value returned by `evaluate_poker_hand`
+pub const HAND_CATEGORY_SHIFT: u32 = 20;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HandCategory {
+    HighCard = 0,
+    OnePair = 1,
+    TwoPair = 2,
+    ThreeOfAKind = 3,
+    Straight = 4,
+    Flush = 5,
+    FullHouse = 6,
+    FourOfAKind = 7,
+    StraightFlush = 8,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Fee percentage too high")]
@@ -921,6 +5357,12 @@ pub enum ErrorCode {
     BetTooSmall,
     #[msg("Cannot check when there are active bets")]
     CannotCheck,
+    #[msg("Betting has not been reopened by a full raise")]
+    BettingNotReopened,
+    #[msg("Bet amount too large for this betting structure")]
+    BetTooLarge,
+    #[msg("The maximum number of raises this street has been reached")]
+    RaiseCapReached,
     #[msg("Not in showdown round")]
     NotShowdownRound,
     #[msg("Game is not finished")]
@@ -929,5 +5371,1036 @@ pub enum ErrorCode {
     CannotLeaveActiveGame,
     #[msg("Player is not at this table")]
     PlayerNotAtTable,
+    #[msg("Player has already committed a shuffle secret")]
+    AlreadyCommitted,
+    #[msg("Invalid shuffle commitment")]
+    InvalidShuffleCommitment,
+    #[msg("Shuffle commitments are not complete")]
+    ShuffleNotCommitted,
+    #[msg("Player has already revealed their shuffle secret")]
+    AlreadyRevealed,
+    #[msg("Revealed secret does not match the commitment")]
+    InvalidShuffleReveal,
+    #[msg("The shuffle reveal window has closed")]
+    RevealWindowClosed,
+    #[msg("The shuffle reveal window is still open")]
+    RevealWindowOpen,
+    #[msg("Not every player has revealed their shuffle secret")]
+    ShuffleIncomplete,
+    #[msg("A shuffle is in progress for the next hand")]
+    ShuffleInProgress,
+    #[msg("This table is dealt by a VRF oracle")]
+    TableUsesVrf,
+    #[msg("This table is not configured with a VRF oracle")]
+    TableDoesNotUseVrf,
+    #[msg("A randomness request is already pending")]
+    RandomnessPending,
+    #[msg("No randomness request is pending")]
+    NoRandomnessRequested,
+    #[msg("The randomness request has not expired")]
+    RandomnessRequestActive,
+    #[msg("Randomness does not match the pending request")]
+    RandomnessRequestMismatch,
+    #[msg("Signer is not the oracle authority for this table")]
+    InvalidOracleAuthority,
+    #[msg("Invalid encryption key")]
+    InvalidEncryptionKey,
+    #[msg("The dealer has not committed a deck for this hand")]
+    DeckNotCommitted,
+    #[msg("The deck for this hand is already committed")]
+    DeckAlreadyCommitted,
+    #[msg("Invalid deck commitment")]
+    InvalidDeckCommitment,
+    #[msg("Encrypted cards are too long")]
+    EncryptedCardsTooLong,
+    #[msg("The cards for this street have already been revealed")]
+    StreetAlreadyRevealed,
+    #[msg("Reveal must contain exactly the cards of the current street")]
+    InvalidStreetReveal,
+    #[msg("The dealer has not revealed this street yet")]
+    StreetNotRevealed,
+    #[msg("Card does not match the deck commitment")]
+    InvalidCardProof,
+    #[msg("Cards have already been revealed")]
+    CardsAlreadyRevealed,
+    #[msg("Reveal must contain exactly the player's hole cards")]
+    InvalidHoleCardReveal,
+    #[msg("The board has not been fully revealed")]
+    BoardNotRevealed,
+    #[msg("Players still have time to reveal their cards")]
+    ShowdownRevealPending,
+    #[msg("Betting is closed while players draw")]
+    DrawInProgress,
+    #[msg("Not in the draw round")]
+    NotDrawRound,
+    #[msg("Player has already drawn this hand")]
+    AlreadyDrawn,
+    #[msg("Invalid discard")]
+    InvalidDiscard,
+    #[msg("Starting stack must be positive")]
+    InvalidStartingStack,
+    #[msg("Invalid blind schedule")]
+    InvalidBlindSchedule,
+    #[msg("Payout table must pay at most one place per seat and add up to 100%")]
+    InvalidPayoutTable,
+    #[msg("Tournament registration is closed")]
+    RegistrationClosed,
+    #[msg("Tournament cannot start until every seat is taken")]
+    TournamentNotFull,
+    #[msg("Tournament chips cannot be cashed out")]
+    TournamentInProgress,
+    #[msg("Tournament is not complete")]
+    TournamentNotComplete,
+    #[msg("Prize has already been claimed")]
+    PrizeAlreadyClaimed,
+    #[msg("No prize for this finishing place")]
+    NoPrize,
+    #[msg("Tournament has not started")]
+    TournamentNotStarted,
+    #[msg("Table is not part of this tournament")]
+    TableNotInTournament,
+    #[msg("Tournament already has the maximum number of tables")]
+    TooManyTables,
+    #[msg("Move would not balance the tournament tables")]
+    TablesUnbalanced,
+    #[msg("A hand is in progress at this table")]
+    GameInProgress,
+    #[msg("Player has not busted")]
+    PlayerNotBusted,
+    #[msg("Busted players must be eliminated first")]
+    EliminationPending,
+    #[msg("Invalid rebuy settings")]
+    InvalidRebuyConfig,
+    #[msg("Rebuys are not available at this table now")]
+    RebuyNotAllowed,
+    #[msg("No rebuys left")]
+    RebuyLimitReached,
+    #[msg("Stack would exceed the table limit")]
+    StackLimitExceeded,
+    #[msg("Top-up amount must be positive")]
+    InvalidTopUp,
+    #[msg("Maximum buy-in must be at least the minimum buy-in")]
+    InvalidBuyInRange,
+    #[msg("Buy-in is outside the range allowed at this table")]
+    InvalidBuyIn,
+    #[msg("Action timeout must be positive")]
+    InvalidActionTimeout,
+    #[msg("No player action is pending")]
+    NoActionPending,
+    #[msg("Player still has time to act")]
+    ActionTimeNotExpired,
+    #[msg("Time bank settings cannot be negative")]
+    InvalidTimeBank,
+    #[msg("Treasury must be owned by the game authority")]
+    InvalidTreasury,
+    #[msg("Fee shares must add up to 10,000 basis points")]
+    InvalidFeeSplit,
+    #[msg("Not enough fees in this bucket")]
+    InsufficientFees,
+    #[msg("Not authorized to change the game configuration")]
+    Unauthorized,
+    #[msg("Invalid authority signer set")]
+    InvalidSignerSet,
+    #[msg("This action is paused")]
+    Paused,
+    #[msg("Unknown pause flags")]
+    InvalidPauseScope,
+    #[msg("Commit window is still open")]
+    CommitWindowOpen,
+    #[msg("Oracle program is not approved")]
+    UnapprovedOracle,
+    #[msg("Too many oracle programs")]
+    TooManyOracles,
+    #[msg("Oracle result account is missing or invalid")]
+    InvalidOracleResult,
+    #[msg("The dealer cannot host or play at the table")]
+    DealerCannotPlay,
+    #[msg("The hand is not waiting on the dealer")]
+    NoRevealPending,
+    #[msg("The dealer still has time to reveal")]
+    DealerRevealWindowOpen,
+    #[msg("The host still has time to act")]
+    HostWindowOpen,
+    #[msg("Dealer is not approved")]
+    UnapprovedDealer,
+    #[msg("Too many dealers")]
+    TooManyDealers,
+    #[msg("Token account is not the vault for this game")]
+    InvalidVault,
+    #[msg("Player can still rebuy")]
+    RebuyAvailable,
+    #[msg("Player state accounts must cover every occupied seat in order")]
+    MissingPlayerState,
+    #[msg("Too many players left recently; try again once their departures expire")]
+    DepartureListFull,
+    #[msg("Account is not this program's game authority")]
+    InvalidGameAuthority,
+    #[msg("Game authority is already in the current layout")]
+    AlreadyMigrated,
+    #[msg("Crank bounty top-up must be positive")]
+    InvalidCrankBounty,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Card of the given rank (0 = two ... 12 = ace) and suit
+    fn card(rank: u8, suit: u8) -> u8 {
+        suit * 13 + rank
+    }
+
+    fn category(value: u32) -> u32 {
+        value >> HAND_CATEGORY_SHIFT
+    }
+
+    /// Every `size`-card combination of `cards`, preserving order
+    fn combinations(cards: &[u8], size: usize) -> Vec<Vec<u8>> {
+        if size == 0 {
+            return vec![Vec::new()];
+        }
+        if cards.len() < size {
+            return Vec::new();
+        }
+        
+        let mut result = Vec::new();
+        for (i, &card) in cards.iter().enumerate() {
+            for mut rest in combinations(&cards[i + 1..], size - 1) {
+                rest.insert(0, card);
+                result.push(rest);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn ranks_every_category_in_order() {
+        let hands = [
+            [card(12, 0), card(11, 1), card(7, 2), card(3, 3), card(1, 0)], // A K 9 5 3
+            [card(8, 0), card(8, 1), card(12, 2), card(5, 3), card(1, 0)],  // T T A 7 3
+            [card(8, 0), card(8, 1), card(3, 2), card(3, 3), card(12, 0)],  // T T 5 5 A
+            [card(4, 0), card(4, 1), card(4, 2), card(12, 3), card(11, 0)], // 6 6 6 A K
+            [card(4, 0), card(5, 1), card(6, 2), card(7, 3), card(8, 0)],   // 6 to T
+            [card(0, 2), card(3, 2), card(6, 2), card(9, 2), card(11, 2)],  // K-high flush
+            [card(1, 0), card(1, 1), card(1, 2), card(0, 3), card(0, 0)],   // 3 3 3 2 2
+            [card(0, 0), card(0, 1), card(0, 2), card(0, 3), card(1, 0)],   // 2 2 2 2 3
+            [card(0, 1), card(1, 1), card(2, 1), card(3, 1), card(4, 1)],   // 2 to 6 suited
+        ];
+        let values: Vec<u32> = hands.iter().map(|hand| evaluate_poker_hand(hand, false)).collect();
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(category(value), i as u32);
+        }
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn wheel_is_the_lowest_straight() {
+        let wheel = evaluate_poker_hand(&[card(12, 0), card(0, 1), card(1, 2), card(2, 3), card(3, 0)], false);
+        let six_high = evaluate_poker_hand(&[card(0, 0), card(1, 1), card(2, 2), card(3, 3), card(4, 0)], false);
+        let broadway = evaluate_poker_hand(&[card(8, 0), card(9, 1), card(10, 2), card(11, 3), card(12, 0)], false);
+        assert_eq!(category(wheel), HandCategory::Straight as u32);
+        assert!(wheel < six_high && six_high < broadway);
+        
+        let steel_wheel = evaluate_poker_hand(&[card(12, 2), card(0, 2), card(1, 2), card(2, 2), card(3, 2)], false);
+        assert_eq!(category(steel_wheel), HandCategory::StraightFlush as u32);
+        
+        // An ace cannot wrap around the top of a straight
+        let wrap = evaluate_poker_hand(&[card(11, 0), card(12, 1), card(0, 2), card(1, 3), card(2, 0)], false);
+        assert_eq!(category(wrap), HandCategory::HighCard as u32);
+    }
+
+    #[test]
+    fn kickers_break_ties() {
+        let aces_king = evaluate_poker_hand(&[card(12, 0), card(12, 1), card(11, 2), card(3, 3), card(1, 0)], false);
+        let aces_queen = evaluate_poker_hand(&[card(12, 2), card(12, 3), card(10, 0), card(9, 1), card(8, 2)], false);
+        assert!(aces_king > aces_queen);
+        
+        let two_pair_ace = evaluate_poker_hand(&[card(8, 0), card(8, 1), card(3, 2), card(3, 3), card(12, 0)], false);
+        let two_pair_king = evaluate_poker_hand(&[card(8, 2), card(8, 3), card(3, 0), card(3, 1), card(11, 0)], false);
+        assert!(two_pair_ace > two_pair_king);
+        
+        // High cards are compared down to the fifth card
+        let fifth_four = evaluate_poker_hand(&[card(12, 0), card(11, 1), card(7, 2), card(3, 3), card(2, 0)], false);
+        let fifth_three = evaluate_poker_hand(&[card(12, 1), card(11, 2), card(7, 3), card(3, 0), card(1, 1)], false);
+        assert!(fifth_four > fifth_three);
+        
+        // Suits never matter, and cards beyond the best five do not play
+        let seven = [card(12, 0), card(12, 1), card(11, 2), card(10, 3), card(9, 0), card(1, 1), card(0, 2)];
+        let five = [card(12, 2), card(12, 3), card(11, 0), card(10, 1), card(9, 2)];
+        assert_eq!(evaluate_poker_hand(&seven, false), evaluate_poker_hand(&five, false));
+        
+        let quads_king = evaluate_poker_hand(&[card(0, 0), card(0, 1), card(0, 2), card(0, 3), card(11, 0)], false);
+        let quads_six = evaluate_poker_hand(&[card(0, 0), card(0, 1), card(0, 2), card(0, 3), card(4, 0)], false);
+        assert!(quads_king > quads_six);
+    }
+
+    #[test]
+    fn two_sets_of_trips_make_a_full_house() {
+        let two_trips = [card(12, 0), card(12, 1), card(12, 2), card(11, 0), card(11, 1), card(11, 2), card(0, 3)];
+        let aces_full = [card(12, 0), card(12, 1), card(12, 2), card(11, 0), card(11, 1)];
+        let value = evaluate_poker_hand(&two_trips, false);
+        assert_eq!(category(value), HandCategory::FullHouse as u32);
+        assert_eq!(value, evaluate_poker_hand(&aces_full, false));
+        
+        // The second set beats a lower pair alongside it
+        let trips_and_pair = [card(4, 0), card(4, 1), card(4, 2), card(3, 0), card(3, 1), card(3, 2), card(2, 3)];
+        let sixes_full_of_fives = [card(4, 0), card(4, 1), card(4, 2), card(3, 0), card(3, 1)];
+        assert_eq!(
+            evaluate_poker_hand(&trips_and_pair, false),
+            evaluate_poker_hand(&sixes_full_of_fives, false)
+        );
+    }
+
+    #[test]
+    fn flush_and_straight_overlap() {
+        // A heart flush alongside an offsuit straight plays as the flush
+        let flush_over_straight = [card(0, 1), card(3, 1), card(5, 1), card(7, 1), card(9, 1), card(6, 0), card(8, 2)];
+        let value = evaluate_poker_hand(&flush_over_straight, false);
+        assert_eq!(category(value), HandCategory::Flush as u32);
+        assert_eq!(value, evaluate_poker_hand(&flush_over_straight[..5], false));
+        
+        // Six suited cards hiding a straight flush below the flush's top card
+        let hidden = [card(12, 3), card(2, 3), card(3, 3), card(4, 3), card(5, 3), card(6, 3), card(12, 0)];
+        let value = evaluate_poker_hand(&hidden, false);
+        assert_eq!(category(value), HandCategory::StraightFlush as u32);
+        assert_eq!(value, evaluate_poker_hand(&hidden[1..6], false));
+        
+        // A straight and a flush that share cards but are not a straight flush
+        let mixed = [card(4, 0), card(5, 0), card(6, 0), card(7, 0), card(8, 1), card(11, 0), card(0, 2)];
+        assert_eq!(category(evaluate_poker_hand(&mixed, false)), HandCategory::Flush as u32);
+    }
+
+    #[test]
+    fn five_card_counts_match_known_totals() {
+        let mut counts = [0u32; 9];
+        let mut distinct = std::collections::HashSet::new();
+        for hand in combinations(&(0..52).collect::<Vec<u8>>(), 5) {
+            let value = evaluate_poker_hand(&hand, false);
+            counts[category(value) as usize] += 1;
+            distinct.insert(value);
+        }
+        assert_eq!(counts, [1_302_540, 1_098_240, 123_552, 54_912, 10_200, 5_108, 3_744, 624, 40]);
+        assert_eq!(distinct.len(), 7_462);
+    }
+
+    #[test]
+    fn omaha_plays_exactly_two_hole_cards() {
+        // Four hearts on the board and one in hand make no flush
+        let hole = [card(12, 1), card(0, 0), card(1, 2), card(2, 3)];
+        let board = [card(11, 1), card(10, 1), card(9, 1), card(7, 1), card(5, 0)];
+        let holdem = evaluate_variant_hand(&GameVariant::Holdem, &hole[..2], &board);
+        let omaha = evaluate_variant_hand(&GameVariant::Omaha, &hole, &board);
+        assert_eq!(category(holdem), HandCategory::Flush as u32);
+        assert_eq!(category(omaha), HandCategory::HighCard as u32);
+        
+        // The best of every two-plus-three hand, checked against a plain enumeration
+        let hole = [card(12, 0), card(12, 1), card(5, 2), card(6, 3)];
+        let board = [card(12, 2), card(7, 2), card(8, 2), card(3, 0), card(4, 1)];
+        let mut best = 0;
+        for pair in combinations(&hole, 2) {
+            for trio in combinations(&board, 3) {
+                best = std::cmp::max(best, evaluate_poker_hand(&[pair.clone(), trio].concat(), false));
+            }
+        }
+        assert_eq!(evaluate_variant_hand(&GameVariant::OmahaHiLo, &hole, &board), best);
+        assert_eq!(category(best), HandCategory::Straight as u32);
+    }
+
+    #[test]
+    fn omaha_low_needs_two_low_hole_cards() {
+        let board = [card(1, 0), card(2, 1), card(6, 2), card(10, 3), card(9, 0)];
+        let wheel_draw = [card(12, 0), card(0, 1), card(11, 2), card(11, 3)];
+        assert_eq!(evaluate_low_hand(&wheel_draw, &board), Some(0x84321));
+        
+        // Paired low hole cards cannot make five distinct ranks
+        let paired = [card(12, 0), card(12, 1), card(11, 2), card(11, 3)];
+        assert_eq!(evaluate_low_hand(&paired, &board), None);
+        let one_low = [card(0, 0), card(9, 1), card(11, 2), card(11, 3)];
+        assert_eq!(evaluate_low_hand(&one_low, &board), None);
+    }
+
+    #[test]
+    fn short_deck_flush_beats_full_house() {
+        let flush = evaluate_poker_hand(&[card(4, 2), card(6, 2), card(8, 2), card(9, 2), card(11, 2)], true);
+        let full_house = evaluate_poker_hand(&[card(12, 0), card(12, 1), card(12, 2), card(11, 0), card(11, 1)], true);
+        let quads = evaluate_poker_hand(&[card(4, 0), card(4, 1), card(4, 2), card(4, 3), card(5, 0)], true);
+        assert!(flush > full_house && quads > flush);
+        
+        // The full deck keeps the usual order
+        let flush = evaluate_poker_hand(&[card(4, 2), card(6, 2), card(8, 2), card(9, 2), card(11, 2)], false);
+        let full_house = evaluate_poker_hand(&[card(12, 0), card(12, 1), card(12, 2), card(11, 0), card(11, 1)], false);
+        assert!(full_house > flush);
+    }
+
+    #[test]
+    fn short_deck_ace_plays_below_the_six() {
+        let low_straight = evaluate_poker_hand(&[card(12, 0), card(4, 1), card(5, 2), card(6, 3), card(7, 0)], true);
+        let seven_high = evaluate_poker_hand(&[card(4, 0), card(5, 1), card(6, 2), card(7, 3), card(8, 0)], true);
+        assert_eq!(category(low_straight), HandCategory::Straight as u32);
+        assert!(low_straight < seven_high);
+        
+        let suited = [card(12, 1), card(4, 1), card(5, 1), card(6, 1), card(7, 1)];
+        assert_eq!(category(evaluate_poker_hand(&suited, true)), HandCategory::StraightFlush as u32);
+        
+        // A-6-7-8-9 is no straight with the full deck
+        let full_deck = evaluate_poker_hand(&[card(12, 0), card(4, 1), card(5, 2), card(6, 3), card(7, 0)], false);
+        assert_eq!(category(full_deck), HandCategory::HighCard as u32);
+    }
+
+    #[test]
+    fn short_deck_counts_match_known_totals() {
+        let mut counts = [0u32; 9];
+        for hand in combinations(&deck_cards(&GameVariant::ShortDeck), 5) {
+            counts[category(evaluate_poker_hand(&hand, true)) as usize] += 1;
+        }
+        // Flushes and full houses trade places in the packed value
+        assert_eq!(counts[HandCategory::StraightFlush as usize], 24);
+        assert_eq!(counts[HandCategory::Straight as usize], 6_120);
+        assert_eq!(counts[HandCategory::FullHouse as usize], 480);
+        assert_eq!(counts[HandCategory::Flush as usize], 1_728);
+        assert_eq!(counts.iter().sum::<u32>(), 376_992);
+    }
+
+    /// Table with an active player in each of `seats`; every other seat is empty
+    fn table_with_seats(max_players: u8, seats: &[usize]) -> (Table, Vec<PlayerState>) {
+        let mut table = Table::deserialize(&mut &vec![0u8; Table::SIZE][..]).unwrap();
+        table.max_players = max_players;
+        table.players = vec![Pubkey::default(); max_players as usize];
+        
+        let mut player_states = Vec::new();
+        for seat in 0..max_players as usize {
+            let mut player_state = PlayerState::deserialize(&mut &vec![0u8; PlayerState::SIZE][..]).unwrap();
+            if seats.contains(&seat) {
+                table.players[seat] = Pubkey::new_unique();
+                player_state.is_active = true;
+                player_state.chips = 100;
+            }
+            player_states.push(player_state);
+        }
+        
+        (table, player_states)
+    }
+
+    fn flagged(flags: &[bool]) -> Vec<usize> {
+        flags.iter().enumerate().filter(|&(_, &flag)| flag).map(|(seat, _)| seat).collect()
+    }
+
+    #[test]
+    fn next_seat_skips_gaps_and_wraps() {
+        let flags = [false, true, false, false, true, false, false, true, false];
+        assert_eq!(next_seat(&flags, 1), Some(4));
+        assert_eq!(next_seat(&flags, 4), Some(7));
+        assert_eq!(next_seat(&flags, 7), Some(1));
+        assert_eq!(next_seat(&flags, 8), Some(1));
+        assert_eq!(next_seat(&flags, 2), Some(4));
+    }
+
+    #[test]
+    fn next_seat_returns_the_only_flagged_seat_last() {
+        let flags = [false, false, true, false];
+        assert_eq!(next_seat(&flags, 2), Some(2));
+        assert_eq!(next_seat(&flags, 0), Some(2));
+        assert_eq!(next_seat(&[false; 6], 3), None);
+    }
+
+    #[test]
+    fn seats_able_to_act_skips_empty_departed_folded_and_all_in_seats() {
+        let (mut table, mut player_states) = table_with_seats(9, &[0, 2, 3, 5, 8]);
+        assert_eq!(flagged(&seats_able_to_act(&table, &player_states)), vec![0, 2, 3, 5, 8]);
+        
+        // Seat 2 leaves the table, seat 3 is still seated but no longer active
+        table.players[2] = Pubkey::default();
+        player_states[3].is_active = false;
+        player_states[5].is_folded = true;
+        player_states[8].is_all_in = true;
+        assert_eq!(flagged(&seats_able_to_act(&table, &player_states)), vec![0]);
+        assert!(betting_closed(&table, &player_states));
+    }
+
+    #[test]
+    fn action_passes_over_gaps_left_by_departed_players() {
+        let (mut table, player_states) = table_with_seats(6, &[1, 3, 4]);
+        table.current_player_index = 1;
+        advance_to_next_player(&mut table, &player_states).unwrap();
+        assert_eq!(table.current_player_index, 3);
+        
+        // Seat 4 leaves before acting, so the action wraps back to seat 1
+        table.players[4] = Pubkey::default();
+        advance_to_next_player(&mut table, &player_states).unwrap();
+        assert_eq!(table.current_player_index, 1);
+    }
+
+    #[test]
+    fn action_passes_over_all_in_players() {
+        let (mut table, mut player_states) = table_with_seats(9, &[0, 4, 6, 7]);
+        player_states[4].is_all_in = true;
+        player_states[6].is_all_in = true;
+        table.current_player_index = 0;
+        advance_to_next_player(&mut table, &player_states).unwrap();
+        assert_eq!(table.current_player_index, 7);
+        assert!(!betting_closed(&table, &player_states));
+        
+        advance_to_next_player(&mut table, &player_states).unwrap();
+        assert_eq!(table.current_player_index, 0);
+    }
+
+    #[test]
+    fn all_in_only_round_leaves_nobody_to_act() {
+        let (mut table, mut player_states) = table_with_seats(6, &[0, 2, 5]);
+        for seat in [0, 2, 5] {
+            player_states[seat].is_all_in = true;
+        }
+        table.current_player_index = 2;
+        assert!(flagged(&seats_able_to_act(&table, &player_states)).is_empty());
+        assert!(betting_closed(&table, &player_states));
+        
+        // With nobody able to act the action stays where it was
+        advance_to_next_player(&mut table, &player_states).unwrap();
+        assert_eq!(table.current_player_index, 2);
+    }
+
+    /// Table where seats 0 and 1 have bet and called 100, a full raise over nothing, with seats
+    /// 2 and 3 still to act
+    fn table_after_bet_and_call() -> (Table, Vec<PlayerState>) {
+        let (mut table, mut player_states) = table_with_seats(6, &[0, 1, 2, 3]);
+        table.highest_bet = 100;
+        table.last_raise_size = 100;
+        table.bets_this_street = 1;
+        for seat in [0, 1] {
+            player_states[seat].has_acted = true;
+            player_states[seat].current_bet = 100;
+            player_states[seat].last_action_bet = 100;
+        }
+        (table, player_states)
+    }
+
+    #[test]
+    fn short_all_in_raise_does_not_reopen_betting() {
+        let (mut table, mut player_states) = table_after_bet_and_call();
+        
+        // Seat 2 is all-in for 150, half a raise
+        record_raise(&mut table, &mut player_states, 2, 150, 50);
+        assert_eq!((table.highest_bet, table.last_raise_size, table.bets_this_street), (150, 100, 1));
+        assert!(player_states[0].has_acted && player_states[1].has_acted);
+        assert!(!betting_reopened(&table, &player_states[0]));
+        assert!(betting_reopened(&table, &player_states[3]));
+        
+        // A second short all-in that brings the bet a full raise above 100 does reopen it
+        record_raise(&mut table, &mut player_states, 3, 200, 50);
+        assert!(betting_reopened(&table, &player_states[0]));
+    }
+
+    #[test]
+    fn full_raise_reopens_betting() {
+        let (mut table, mut player_states) = table_after_bet_and_call();
+        
+        record_raise(&mut table, &mut player_states, 2, 250, 150);
+        assert_eq!((table.highest_bet, table.last_raise_size, table.bets_this_street), (250, 150, 2));
+        assert!(!player_states[0].has_acted && !player_states[1].has_acted);
+        assert!(betting_reopened(&table, &player_states[0]));
+    }
+
+    /// Pots built from `(seat, contributed, still_in_hand)` entries, as amounts and eligible seats
+    fn pots(contributions: &[(usize, u64, bool)]) -> Vec<(u64, Vec<usize>)> {
+        build_pots(contributions)
+            .into_iter()
+            .map(|pot| (pot.amount, pot.eligible_seats))
+            .collect()
+    }
+
+    #[test]
+    fn three_way_all_in_builds_a_side_pot_per_stack() {
+        let contributions = [(0, 100, true), (1, 250, true), (2, 400, true)];
+        assert_eq!(pots(&contributions), vec![(300, vec![0, 1, 2]), (300, vec![1, 2]), (150, vec![2])]);
+        
+        // Nobody matched the biggest stack's last 150, so it goes back before the pots are built
+        assert_eq!(uncalled_excess(&contributions), Some((2, 150)));
+        let called = [(0, 100, true), (1, 250, true), (2, 250, true)];
+        assert_eq!(pots(&called), vec![(300, vec![0, 1, 2]), (300, vec![1, 2])]);
+        assert_eq!(uncalled_excess(&called), None);
+    }
+
+    #[test]
+    fn folded_chips_are_dead_money_in_the_pots_they_reached() {
+        // A fold below the live players' level only adds to the main pot
+        assert_eq!(pots(&[(0, 50, false), (1, 200, true), (2, 200, true)]), vec![(450, vec![1, 2])]);
+        
+        // Chips folded above the highest live contribution go to the last pot
+        let contributions = [(0, 300, false), (1, 100, true), (2, 200, true)];
+        assert_eq!(pots(&contributions), vec![(300, vec![1, 2]), (300, vec![2])]);
+        let total: u64 = pots(&contributions).iter().map(|(amount, _)| amount).sum();
+        assert_eq!(total, 600);
+    }
+
+    #[test]
+    fn departures_leave_the_list_only_once_expired() {
+        let (mut table, _) = table_with_seats(6, &[]);
+        let players: Vec<Pubkey> = (0..MAX_RECENT_DEPARTURES).map(|_| Pubkey::new_unique()).collect();
+        for (i, player) in players.iter().enumerate() {
+            record_departure(&mut table, *player, 100 + i as u64, i as i64);
+        }
+        assert_eq!(table.recent_departures.len(), MAX_RECENT_DEPARTURES);
+        
+        // The oldest departure is still remembered until its window runs out
+        assert_eq!(take_recent_departure(&mut table, &players[0], RATHOLE_WINDOW - 1), Some(100));
+        assert_eq!(take_recent_departure(&mut table, &players[1], RATHOLE_WINDOW + 1), None);
+        assert_eq!(table.recent_departures.len(), MAX_RECENT_DEPARTURES - 2);
+    }
+
+    #[test]
+    fn uncalled_bet_is_returned() {
+        assert_eq!(uncalled_excess(&[(0, 100, true), (1, 400, true)]), Some((1, 300)));
+        assert_eq!(uncalled_excess(&[(0, 400, true), (1, 400, true)]), None);
+        assert_eq!(uncalled_excess(&[(3, 250, true)]), Some((3, 250)));
+        assert_eq!(uncalled_excess(&[]), None);
+    }
+
+    #[test]
+    fn all_in_for_less_than_the_call_caps_the_main_pot() {
+        // Two players cover the bet, so nothing is returned and the short stack plays the main pot
+        let contributions = [(0, 300, true), (1, 120, true), (2, 300, true)];
+        assert_eq!(uncalled_excess(&contributions), None);
+        assert_eq!(pots(&contributions), vec![(360, vec![0, 1, 2]), (360, vec![0, 2])]);
+        
+        // Heads-up, the bettor gets back what the short stack could not call
+        let heads_up = [(0, 300, true), (1, 120, true)];
+        assert_eq!(uncalled_excess(&heads_up), Some((0, 180)));
+        assert_eq!(pots(&[(0, 120, true), (1, 120, true)]), vec![(240, vec![0, 1])]);
+    }
+
+    #[test]
+    fn hands_ending_in_the_first_round_are_not_raked() {
+        let (mut table, _) = table_with_seats(6, &[0, 1]);
+        table.rake_percentage = 5;
+        for round in [Round::PreFlop, Round::PreDraw, Round::ThirdStreet] {
+            table.round = round;
+            assert_eq!(take_rake(&mut table, 1000), 0);
+        }
+        
+        table.round = Round::Flop;
+        table.game_mode = GameMode::SitAndGo;
+        assert_eq!(take_rake(&mut table, 1000), 0);
+        
+        table.game_mode = GameMode::CashGame;
+        assert_eq!(take_rake(&mut table, 1000), 50);
+    }
+
+    #[test]
+    fn rake_stops_at_the_cap_across_a_hands_pots() {
+        let (mut table, _) = table_with_seats(6, &[0, 1, 2]);
+        table.round = Round::River;
+        table.big_blind = 10;
+        table.rake_percentage = 5;
+        table.rake_cap_big_blinds = 3;
+        
+        assert_eq!(take_rake(&mut table, 400), 20);
+        assert_eq!(take_rake(&mut table, 400), 10);
+        assert_eq!(take_rake(&mut table, 400), 0);
+        assert_eq!(table.rake, 30);
+        assert_eq!(table.rake_due, 30);
+    }
+
+    #[test]
+    fn host_share_is_set_aside_as_the_rake_is_taken() {
+        let (mut table, _) = table_with_seats(6, &[0, 1]);
+        table.round = Round::Turn;
+        table.rake_percentage = 5;
+        table.host_fee_bps = 2_000;
+        
+        assert_eq!(take_rake(&mut table, 1000), 50);
+        assert_eq!(table.host_rake_due, 10);
+        assert_eq!(table.rake_due, 40);
+    }
+
+    #[test]
+    fn split_pot_gives_odd_chips_left_of_the_dealer() {
+        // Dealer on seat 4: seat 6 is first to the left, then seat 1 after wrapping
+        assert_eq!(split_pot(101, &[1, 6], 4, 9), vec![(6, 51), (1, 50)]);
+        assert_eq!(split_pot(101, &[6, 1], 4, 9), vec![(6, 51), (1, 50)]);
+        assert_eq!(split_pot(11, &[0, 3, 8], 2, 9), vec![(3, 4), (8, 4), (0, 3)]);
+        
+        // The dealer is last in line for an odd chip
+        assert_eq!(split_pot(5, &[2, 4], 2, 6), vec![(4, 3), (2, 2)]);
+        assert_eq!(split_pot(100, &[1, 2, 3, 4], 0, 6), vec![(1, 25), (2, 25), (3, 25), (4, 25)]);
+        assert!(split_pot(100, &[], 0, 6).is_empty());
+    }
+
+    #[test]
+    fn hi_lo_high_half_keeps_the_odd_chip() {
+        assert_eq!(split_hi_lo(101, &[3], &[5], 0, 6), vec![(3, 51), (5, 50)]);
+        
+        // A scoop of both halves still totals the pot
+        let shares = split_hi_lo(101, &[3], &[3], 0, 6);
+        assert_eq!(shares, vec![(3, 51), (3, 50)]);
+        assert_eq!(shares.iter().map(|&(_, share)| share).sum::<u64>(), 101);
+    }
+
+    #[test]
+    fn hi_lo_halves_split_their_odd_chips_left_of_the_dealer() {
+        // 103 gives a 52 high half and a 51 low half, each shared by two players
+        let shares = split_hi_lo(103, &[1, 4], &[2, 5], 3, 6);
+        assert_eq!(shares, vec![(4, 26), (1, 26), (5, 26), (2, 25)]);
+        assert_eq!(shares.iter().map(|&(_, share)| share).sum::<u64>(), 103);
+    }
+
+    #[test]
+    fn hi_lo_high_hand_scoops_without_a_qualifying_low() {
+        assert_eq!(split_hi_lo(101, &[2], &[], 0, 6), vec![(2, 101)]);
+        assert_eq!(split_hi_lo(101, &[2, 4], &[], 0, 6), vec![(2, 51), (4, 50)]);
+    }
+
+    #[test]
+    fn payouts_fit_a_short_field() {
+        let payouts = [5_000, 3_000, 2_000];
+        assert_eq!(fit_payouts(&payouts, 9), vec![5_000, 3_000, 2_000]);
+        assert_eq!(fit_payouts(&payouts, 3), vec![5_000, 3_000, 2_000]);
+        
+        // 5000:3000 scaled to the pool rounds down to 6250 and 3750 exactly
+        assert_eq!(fit_payouts(&payouts, 2), vec![6_250, 3_750]);
+        
+        // 4000:3500 scales to 5333 and 4666, so first place takes the leftover point
+        let fitted = fit_payouts(&[4_000, 3_500, 2_500], 2);
+        assert_eq!(fitted, vec![5_334, 4_666]);
+        assert_eq!(fitted.iter().map(|&bps| bps as u32).sum::<u32>(), PAYOUT_BPS_TOTAL as u32);
+        
+        // The whole pool is paid out with nothing left in the vault
+        let pool = 1_000_003;
+        let paid: u64 = (1..=fitted.len()).map(|place| prize_for_place(pool, &fitted, place)).sum();
+        assert_eq!(paid, pool);
+    }
+
+    #[test]
+    fn draws_replace_only_the_discarded_cards_from_the_undealt_deck() {
+        let (mut table, mut player_states) = table_with_seats(6, &[0, 1]);
+        table.game_variant = GameVariant::FiveCardDraw;
+        table.deck_seed = 42;
+        table.deck_position = 10;
+        let player_state = &mut player_states[0];
+        player_state.hole_positions[..5].copy_from_slice(&[0, 1, 2, 3, 4]);
+        player_state.encrypted_cards = vec![1; 8];
+        
+        // Standing pat keeps the hand and the delivered cards
+        draw_replacements(&mut table, player_state, 0);
+        assert_eq!(player_state.hole_positions[..5], [0, 1, 2, 3, 4]);
+        assert_eq!(table.deck_position, 10);
+        assert!(!player_state.encrypted_cards.is_empty());
+        
+        // Discarding the first and fourth cards takes the next two positions in deck order
+        let deck = generate_shuffled_deck(42, 52);
+        draw_replacements(&mut table, player_state, 0b01001);
+        assert_eq!(player_state.hole_positions[..5], [deck[10], 1, 2, deck[11], 4]);
+        assert_eq!(table.deck_position, 12);
+        assert!(player_state.encrypted_cards.is_empty());
+    }
+
+    #[test]
+    fn draw_passes_to_the_next_player_still_in_the_hand() {
+        let (mut table, mut player_states) = table_with_seats(6, &[0, 2, 3, 5]);
+        table.round = Round::Draw;
+        player_states[2].is_folded = true;
+        player_states[3].is_all_in = true;
+        assert_eq!(flagged(&seats_to_draw(&table, &player_states)), vec![0, 3, 5]);
+        
+        // All-in players still draw; folded players are passed over
+        table.current_player_index = 0;
+        player_states[0].has_acted = true;
+        finish_draw(&mut table, &mut player_states).unwrap();
+        assert_eq!(table.current_player_index, 3);
+        assert!(table.round == Round::Draw);
+        
+        player_states[3].has_acted = true;
+        finish_draw(&mut table, &mut player_states).unwrap();
+        assert_eq!(table.current_player_index, 5);
+        
+        // Once the last player has drawn nobody is left and the round can close
+        player_states[5].has_acted = true;
+        assert!(flagged(&seats_to_draw(&table, &player_states)).is_empty());
+    }
+
+    #[test]
+    fn bring_in_comes_from_the_lowest_upcard_by_rank_then_suit() {
+        let (mut table, mut player_states) = table_with_seats(6, &[0, 2, 4, 5]);
+        table.small_blind = 5;
+        player_states[0].upcards[0] = card(3, 0);
+        player_states[2].upcards[0] = card(1, 2);
+        player_states[4].upcards[0] = card(1, 1);
+        player_states[5].upcards[0] = card(0, 3);
+        
+        // The lowest deuce would bring it in, but seat 5 is all-in, so it goes to the club three
+        player_states[5].is_all_in = true;
+        post_bring_in(&mut table, &mut player_states);
+        assert_eq!(player_states[4].current_bet, 5);
+        assert_eq!(player_states[4].chips, 95);
+        assert!(!player_states[4].has_acted);
+        assert_eq!((table.pot, table.highest_bet), (5, 5));
+        assert_eq!(table.current_player_index, 0);
+    }
+
+    #[test]
+    fn stud_street_opens_with_the_best_visible_hand() {
+        let (mut table, mut player_states) = table_with_seats(6, &[1, 3, 4]);
+        table.dealer_index = 4;
+        let upcards = [
+            (1, [card(3, 0), card(3, 1)]),   // 5 5
+            (3, [card(3, 2), card(3, 3)]),   // 5 5
+            (4, [card(12, 0), card(11, 0)]), // A K
+        ];
+        for (seat, cards) in upcards {
+            player_states[seat].upcards[..2].copy_from_slice(&cards);
+            player_states[seat].upcards_revealed = 2;
+        }
+        
+        // The pairs tie, so the one closer to the dealer's left opens
+        assert_eq!(stud_opener(&table, &player_states), Some(1));
+        table.dealer_index = 1;
+        assert_eq!(stud_opener(&table, &player_states), Some(3));
+        
+        // An all-in opener passes the action on; a folded hand is not shown
+        player_states[3].is_all_in = true;
+        assert_eq!(stud_opener(&table, &player_states), Some(4));
+        player_states[1].is_folded = true;
+        player_states[3].is_folded = true;
+        assert_eq!(stud_opener(&table, &player_states), Some(4));
+    }
+
+    #[test]
+    fn sit_and_go_places_busted_players_by_starting_stack() {
+        let (mut table, mut player_states) = table_with_seats(6, &[0, 2, 3, 5]);
+        table.status = TableStatus::Finished;
+        table.player_count = 4;
+        for seat in [0, 2, 3, 5] {
+            player_states[seat].player = table.players[seat];
+        }
+        
+        // Seats 0 and 5 bust in the same hand; seat 5 started it with less and goes out first
+        for (seat, contributed) in [(0, 300), (5, 200)] {
+            player_states[seat].chips = 0;
+            player_states[seat].total_contributed = contributed;
+        }
+        eliminate_busted_players(&mut table, &mut player_states);
+        assert_eq!((player_states[5].finishing_place, player_states[0].finishing_place), (4, 3));
+        assert!(table.players[0] == Pubkey::default() && !player_states[0].is_active);
+        assert_eq!(table.player_count, 2);
+        assert!(table.status == TableStatus::Finished);
+        
+        // The last player standing is never eliminated and wins
+        player_states[2].chips = 0;
+        eliminate_busted_players(&mut table, &mut player_states);
+        assert_eq!(player_states[2].finishing_place, 2);
+        assert_eq!(tournament_place(player_states[3].finishing_place), 1);
+        assert!(table.status == TableStatus::Completed);
+    }
+
+    /// Running tournament of 6-seat tables with the given player counts
+    fn tournament_with_tables(counts: &[u8]) -> Tournament {
+        let mut tournament = Tournament::deserialize(&mut &vec![0u8; Tournament::SIZE][..]).unwrap();
+        tournament.seats_per_table = 6;
+        tournament.tables = counts.iter().map(|_| Pubkey::new_unique()).collect();
+        tournament.table_counts = counts.to_vec();
+        tournament.players_remaining = counts.iter().map(|&count| count as u16).sum();
+        tournament
+    }
+
+    #[test]
+    fn players_only_move_to_a_table_two_players_shorter() {
+        let mut tournament = tournament_with_tables(&[6, 5, 4]);
+        let tables = tournament.tables.clone();
+        assert!(move_table_count(&mut tournament, Some(&tables[0]), &tables[1]).is_err());
+        move_table_count(&mut tournament, Some(&tables[0]), &tables[2]).unwrap();
+        assert_eq!(tournament.table_counts, vec![5, 5, 5]);
+        assert!(move_table_count(&mut tournament, Some(&tables[1]), &tables[2]).is_err());
+        
+        // A broken table is off the counts, so its players can go to any table
+        move_table_count(&mut tournament, None, &tables[1]).unwrap();
+        assert_eq!(tournament.table_counts, vec![5, 6, 5]);
+        assert!(move_table_count(&mut tournament, None, &Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn only_an_emptiest_table_breaks_once_its_players_fit_elsewhere() {
+        let mut tournament = tournament_with_tables(&[5, 4, 4]);
+        let tables = tournament.tables.clone();
+        
+        // 13 players do not fit in two tables of six
+        assert!(remove_tournament_table(&mut tournament, &tables[1]).is_err());
+        tournament.table_counts = vec![5, 3, 4];
+        tournament.players_remaining = 12;
+        assert!(remove_tournament_table(&mut tournament, &tables[2]).is_err());
+        remove_tournament_table(&mut tournament, &tables[1]).unwrap();
+        assert_eq!(tournament.tables, vec![tables[0], tables[2]]);
+        assert_eq!(tournament.table_counts, vec![5, 4]);
+        assert!(tournament.final_table == Pubkey::default());
+        
+        // Breaking down to one table makes it the final table
+        tournament.players_remaining = 6;
+        tournament.table_counts = vec![4, 2];
+        remove_tournament_table(&mut tournament, &tables[2]).unwrap();
+        assert!(tournament.final_table == tables[0]);
+    }
+
+    #[test]
+    fn timeout_reward_that_empties_the_stack_leaves_the_player_all_in() {
+        let (mut table, mut player_states) = table_with_seats(6, &[0, 2, 4]);
+        table.game_mode = GameMode::CashGame;
+        table.big_blind = 100;
+        table.current_player_index = 2;
+        
+        // The reward is 5% of the big blind; a stack of 3 is emptied mid-hand
+        assert_eq!(take_timeout_reward(&table, &mut player_states[0]), 5);
+        assert_eq!(player_states[0].chips, 95);
+        player_states[2].chips = 3;
+        assert_eq!(take_timeout_reward(&table, &mut player_states[2]), 3);
+        assert_eq!(player_states[2].chips, 0);
+        assert!(player_states[2].is_all_in);
+        assert_eq!(flagged(&seats_able_to_act(&table, &player_states)), vec![0, 4]);
+        advance_to_next_player(&mut table, &player_states).unwrap();
+        assert_eq!(table.current_player_index, 4);
+        
+        // A folded player is out of the hand rather than all-in
+        player_states[4].chips = 5;
+        player_states[4].is_folded = true;
+        assert_eq!(take_timeout_reward(&table, &mut player_states[4]), 5);
+        assert!(!player_states[4].is_all_in);
+        
+        // Tournament chips pay nothing
+        table.game_mode = GameMode::SitAndGo;
+        assert_eq!(take_timeout_reward(&table, &mut player_states[0]), 0);
+        assert_eq!(player_states[0].chips, 95);
+    }
+
+    #[test]
+    fn time_bank_pays_only_for_time_past_the_action_window() {
+        let (mut table, mut player_states) = table_with_seats(6, &[0, 1]);
+        table.turn_started_at = 1_000;
+        table.action_timeout = 30;
+        table.time_bank = 60;
+        table.time_bank_refill = 20;
+        let player_state = &mut player_states[0];
+        player_state.time_bank = 60;
+        
+        // Acting inside the window costs nothing
+        charge_time_bank(&table, player_state, 1_030);
+        assert_eq!(player_state.time_bank, 60);
+        charge_time_bank(&table, player_state, 1_045);
+        assert_eq!(player_state.time_bank, 45);
+        
+        // Running past the bank empties it without going negative
+        charge_time_bank(&table, player_state, 1_200);
+        assert_eq!(player_state.time_bank, 0);
+        
+        // Refills top the bank back up to the table's starting size and no further
+        refill_time_bank(&table, player_state);
+        assert_eq!(player_state.time_bank, 20);
+        player_state.time_bank = 50;
+        refill_time_bank(&table, player_state);
+        assert_eq!(player_state.time_bank, 60);
+    }
+
+    #[test]
+    fn authority_signers_must_reach_the_threshold() {
+        let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let mut lamports = vec![0u64; keys.len()];
+        let mut data: Vec<Vec<u8>> = vec![Vec::new(); keys.len()];
+        let owner = Pubkey::default();
+        let accounts: Vec<AccountInfo> = keys
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .enumerate()
+            .map(|(i, ((key, lamports), data))| AccountInfo::new(key, i != 3, false, lamports, data, &owner, false, 0))
+            .collect();
+        let authority = Signer::try_from(&accounts[0]).unwrap();
+        
+        // Without a signer set only the authority itself is accepted
+        let legacy = LegacyGameAuthority {
+            authority: keys[1],
+            fee_percentage: 5,
+            total_games_played: 0,
+            total_fees_collected: 0,
+            bump: 255,
+        };
+        let mut game_authority = migrated_game_authority(&legacy, Pubkey::default());
+        assert!(verify_authority(&game_authority, &authority, &[]).is_err());
+        assert!(verify_authority(&game_authority, &Signer::try_from(&accounts[1]).unwrap(), &[]).is_ok());
+        
+        // Two of three: a repeated signer counts once and an account that did not sign not at all
+        game_authority.signers = keys[..3].to_vec();
+        game_authority.threshold = 2;
+        assert!(verify_authority(&game_authority, &authority, &[]).is_err());
+        assert!(verify_authority(&game_authority, &authority, &[accounts[0].clone()]).is_err());
+        assert!(verify_authority(&game_authority, &authority, &[accounts[3].clone()]).is_err());
+        assert!(verify_authority(&game_authority, &authority, &[accounts[2].clone()]).is_ok());
+        
+        // Once a signer set is in place the authority key alone no longer suffices
+        game_authority.signers = vec![keys[0], keys[2]];
+        assert!(verify_authority(&game_authority, &Signer::try_from(&accounts[1]).unwrap(), &[]).is_err());
+    }
+
+    #[test]
+    fn first_release_authority_migrates_once() {
+        let legacy = LegacyGameAuthority {
+            authority: Pubkey::new_unique(),
+            fee_percentage: 5,
+            total_games_played: 42,
+            total_fees_collected: 1_000,
+            bump: 254,
+        };
+        let mut data = <GameAuthority as anchor_lang::Discriminator>::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        assert!(read_legacy_game_authority(&[&[0u8; 8], &data[8..]].concat()).is_err());
+        
+        // The old fields carry over and the new settings start as `initialize` leaves them
+        let fee_mint = Pubkey::new_unique();
+        let game_authority = migrated_game_authority(&read_legacy_game_authority(&data).unwrap(), fee_mint);
+        let mut migrated = vec![0u8; 8 + GameAuthority::SIZE];
+        game_authority.try_serialize(&mut &mut migrated[..]).unwrap();
+        let game_authority = GameAuthority::try_deserialize(&mut &migrated[..]).unwrap();
+        assert!(game_authority.authority == legacy.authority && game_authority.fee_mint == fee_mint);
+        assert_eq!((game_authority.fee_percentage, game_authority.bump), (5, 254));
+        assert_eq!((game_authority.total_games_played, game_authority.total_fees_collected), (42, 1_000));
+        assert_eq!(game_authority.protocol_fee_bps, 10_000);
+        assert!(game_authority.signers.is_empty() && !game_authority.paused);
+        
+        // An account already in the new layout is refused
+        assert!(read_legacy_game_authority(&migrated).is_err());
+    }
+
+    #[test]
+    fn pause_refuses_only_the_actions_in_its_scope() {
+        let mut game_authority = GameAuthority::deserialize(&mut &vec![0u8; GameAuthority::SIZE][..]).unwrap();
+        let scopes = [PAUSE_CREATE, PAUSE_JOIN, PAUSE_START];
+        
+        // A scope on its own does nothing until the pause is switched on
+        game_authority.pause_scope = PAUSE_ALL;
+        assert!(scopes.iter().all(|&scope| require_not_paused(&game_authority, scope).is_ok()));
+        
+        game_authority.paused = true;
+        game_authority.pause_scope = PAUSE_JOIN | PAUSE_START;
+        assert!(require_not_paused(&game_authority, PAUSE_CREATE).is_ok());
+        assert!(require_not_paused(&game_authority, PAUSE_JOIN).is_err());
+        assert!(require_not_paused(&game_authority, PAUSE_START).is_err());
+        
+        game_authority.pause_scope = PAUSE_ALL;
+        assert!(scopes.iter().all(|&scope| require_not_paused(&game_authority, scope).is_err()));
+        
+        // A pause has to name what it stops, so an empty scope is refused up front
+        assert!(validate_pause_scope(true, 0).is_err());
+        assert!(validate_pause_scope(true, PAUSE_ALL << 1).is_err());
+        assert!(validate_pause_scope(true, PAUSE_START).is_ok());
+        assert!(validate_pause_scope(false, 0).is_ok());
+    }
+
+    #[test]
+    fn crank_bounty_pays_until_it_runs_out() {
+        let (mut table, _) = table_with_seats(6, &[0, 1]);
+        table.crank_bounty = 2 * TIMEOUT_CRANK_BOUNTY + 7;
+        assert_eq!(take_crank_bounty(&mut table), TIMEOUT_CRANK_BOUNTY);
+        assert_eq!(take_crank_bounty(&mut table), TIMEOUT_CRANK_BOUNTY);
+        
+        // The last crank takes what is left, and an empty bounty pays nothing
+        assert_eq!(take_crank_bounty(&mut table), 7);
+        assert_eq!(take_crank_bounty(&mut table), 0);
+        assert_eq!(table.crank_bounty, 0);
+    }
+}